@@ -0,0 +1,86 @@
+use crate::{
+    auth_basic::decode_basic,
+    auth_bearer::{bearer_from_parts, has_access_token, query_allowed},
+    header::auth_header,
+    AuthBasic, AuthConfig, AuthError, AuthRejection, Secret,
+};
+use async_trait::async_trait;
+use axum_core::extract::FromRequestParts;
+use http::request::Parts;
+
+/// Extractor which accepts either basic authentication or a bearer token, depending on what the client sent
+///
+/// This is enabled when both the `auth-basic` and `auth-bearer` features are.
+///
+/// The `Authorization` header is only parsed once and handed to the scheme it's for, so routes can serve both legacy clients sending passwords and newer ones sending tokens without the rejections of one getting muddled with the other.
+///
+/// # Example
+///
+/// ```
+/// use axum::{extract::FromRequestParts, http::Request};
+/// use axum_auth::AuthAny;
+///
+/// /// Handler which greets users however they logged in
+/// async fn handler(auth: AuthAny) -> String {
+///     match auth {
+///         AuthAny::Basic(id, _) => format!("Hello, {}", id),
+///         AuthAny::Bearer(token) => format!("Found a bearer token: {}", token.expose_secret()),
+///     }
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let (mut parts, _) = Request::builder()
+///     .header("Authorization", "Bearer s3cr3t")
+///     .body(())
+///     .unwrap()
+///     .into_parts();
+///
+/// let auth = AuthAny::from_request_parts(&mut parts, &()).await.unwrap();
+/// assert_eq!(auth, AuthAny::Bearer("s3cr3t".into()));
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// This gives off the same errors as [AuthBasic](crate::AuthBasic), decoding credentials using the [BasicCharset](crate::BasicCharset) set in the [AuthConfig], and [AuthBearer](crate::AuthBearer) depending on the scheme used, or [AuthError::WrongScheme] if it's neither. Bearer tokens are read from the query just like [AuthBearer](crate::AuthBearer) does, and sending one alongside basic credentials gives [AuthError::DuplicateToken]. Every error is sent as a single `401 Unauthorized` with both a `WWW-Authenticate: Basic realm="...", charset="UTF-8"` and a `WWW-Authenticate: Bearer realm="..."` challenge, the latter only getting an error code if a bearer token was sent.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AuthAny {
+    /// Basic authentication, containing an identifier as well as an optional password
+    Basic(String, Option<Secret>),
+    /// Bearer token
+    Bearer(Secret),
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthAny
+where
+    S: Send + Sync,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // Send the header to whichever scheme it's for, remembering if it was a bearer token for the challenge
+        let config = AuthConfig::from_parts(parts);
+        let query = query_allowed(&parts.extensions, &config)
+            && parts.uri.query().is_some_and(has_access_token);
+        let (result, bearer) = match auth_header(&parts.headers, &config) {
+            Ok(("Basic", _)) if query => (Err(AuthError::DuplicateToken), true),
+            Ok(("Basic", contents)) => (
+                decode_basic(contents, &config)
+                    .map(|AuthBasic((id, password))| Self::Basic(id, password)),
+                false,
+            ),
+            Ok(("Bearer", _)) => (bearer_from_parts(parts, &config).map(Self::Bearer), true),
+            _ if query => (bearer_from_parts(parts, &config).map(Self::Bearer), true),
+            Ok(_) => (
+                Err(AuthError::WrongScheme {
+                    expected: "Basic or Bearer",
+                }),
+                false,
+            ),
+            Err(err) => (Err(err), false),
+        };
+        result.map_err(|err| config.reject_any(err, bearer))
+    }
+}
@@ -0,0 +1,351 @@
+use crate::{
+    auth_bearer::QueryTokenAllowed, header::cookie, AuthConfig, AuthError, AuthRejection,
+    BearerValidator, Secret,
+};
+use async_trait::async_trait;
+use axum_core::extract::{FromRef, FromRequestParts};
+use http::{header::HeaderName, request::Parts};
+use std::{borrow::Cow, fmt, marker::PhantomData};
+
+/// Place in a request which an API key is read from
+///
+/// This is enabled via the `auth-api-key` feature.
+///
+/// This can be put into your router's state to pick where [AuthApiKey] and [ValidatedApiKey] look at runtime, or returned from your own [ApiKeySource] to pick it at the type level.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ApiKeyLocation {
+    /// Header with the given name, e.g. `X-API-Key`
+    Header(HeaderName),
+    /// Query parameter with the given name, e.g. `api_key`
+    Query(Cow<'static, str>),
+    /// Cookie with the given name
+    Cookie(Cow<'static, str>),
+}
+
+impl ApiKeyLocation {
+    /// Finds the API key in a request
+    ///
+    /// Keys which aren't there give [AuthError::MissingApiKey], whilst keys which are empty, contain anything but visible ASCII or are given more than once give [AuthError::MalformedApiKey]. Cookies are the exception to the last rule as browsers may legitimately send several with the same name, so the first one is used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum::http::Request;
+    /// use axum_auth::{ApiKeyLocation, AuthError};
+    ///
+    /// let (parts, _) = Request::builder()
+    ///     .uri("/?api_key=s3cr3t")
+    ///     .header("Cookie", "theme=dark; session=abc")
+    ///     .body(())
+    ///     .unwrap()
+    ///     .into_parts();
+    ///
+    /// let query = ApiKeyLocation::Query("api_key".into());
+    /// assert_eq!(query.find(&parts), Ok("s3cr3t".to_string()));
+    ///
+    /// let cookie = ApiKeyLocation::Cookie("session".into());
+    /// assert_eq!(cookie.find(&parts), Ok("abc".to_string()));
+    ///
+    /// let header = ApiKeyLocation::Header("x-api-key".parse().unwrap());
+    /// assert_eq!(header.find(&parts), Err(AuthError::MissingApiKey));
+    /// ```
+    pub fn find(&self, parts: &Parts) -> Result<String, AuthError> {
+        let key = match self {
+            Self::Header(name) => single(parts.headers.get_all(name).iter())?
+                .map(|value| value.to_str().map(str::to_string))
+                .transpose()
+                .map_err(|_| AuthError::MalformedApiKey)?,
+            Self::Query(name) => single(
+                form_urlencoded::parse(parts.uri.query().unwrap_or_default().as_bytes())
+                    .filter(|(key, _)| key == name)
+                    .map(|(_, value)| value.into_owned()),
+            )?,
+            Self::Cookie(name) => cookie(&parts.headers, name).map(str::to_string),
+        };
+
+        // Only allow keys which could've been sent in any location
+        match key {
+            Some(key) if !key.is_empty() && key.bytes().all(|b| b.is_ascii_graphic()) => Ok(key),
+            Some(_) => Err(AuthError::MalformedApiKey),
+            None => Err(AuthError::MissingApiKey),
+        }
+    }
+
+    /// Checks if a key in a request is longer than `max_len` as sent, without decoding or copying it
+    fn exceeds(&self, parts: &Parts, max_len: usize) -> bool {
+        match self {
+            Self::Header(name) => {
+                (parts.headers.get_all(name).iter()).any(|value| value.len() > max_len)
+            }
+            Self::Query(name) => (parts.uri.query().unwrap_or_default().split('&')).any(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                value.len() > max_len
+                    && form_urlencoded::parse(key.as_bytes()).any(|(key, _)| key == *name)
+            }),
+            Self::Cookie(name) => {
+                cookie(&parts.headers, name).is_some_and(|key| key.len() > max_len)
+            }
+        }
+    }
+}
+
+/// Gets the only item out of `items`, erroring if there's more than one
+fn single<T>(mut items: impl Iterator<Item = T>) -> Result<Option<T>, AuthError> {
+    match (items.next(), items.next()) {
+        (first, None) => Ok(first),
+        _ => Err(AuthError::MalformedApiKey),
+    }
+}
+
+/// Source of the [location](ApiKeyLocation) which [AuthApiKey] and [ValidatedApiKey] read keys from
+///
+/// This is enabled via the `auth-api-key` feature.
+///
+/// This crate comes with [XApiKey], [ApiKeyQuery] and [ApiKeyCookie] for the usual locations. [ApiKeyLocation] itself also implements this by taking itself out of your router's state using [FromRef], so the location can be picked at runtime.
+///
+/// # Example
+///
+/// Picking a custom header at the type level is done by implementing this for your own type:
+///
+/// ```no_run
+/// use axum::http::HeaderName;
+/// use axum_auth::{ApiKeyLocation, ApiKeySource, AuthApiKey};
+///
+/// /// Source which reads the header our internal services send
+/// struct ServiceToken;
+///
+/// impl<S> ApiKeySource<S> for ServiceToken {
+///     fn location(_state: &S) -> ApiKeyLocation {
+///         ApiKeyLocation::Header(HeaderName::from_static("x-service-token"))
+///     }
+/// }
+///
+/// async fn handler(AuthApiKey(key, _): AuthApiKey<ServiceToken>) -> String {
+///     format!("Found a service token: {}", key.expose_secret())
+/// }
+/// ```
+pub trait ApiKeySource<S> {
+    /// Gets the location to read keys from
+    fn location(state: &S) -> ApiKeyLocation;
+}
+
+/// Source which reads API keys from the `X-API-Key` header, used by default
+///
+/// This is enabled via the `auth-api-key` feature.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct XApiKey;
+
+impl<S> ApiKeySource<S> for XApiKey {
+    fn location(_state: &S) -> ApiKeyLocation {
+        ApiKeyLocation::Header(HeaderName::from_static("x-api-key"))
+    }
+}
+
+/// Source which reads API keys from the `api_key` query parameter
+///
+/// This is enabled via the `auth-api-key` feature.
+///
+/// Keys in URLs tend to end up in logs and browser histories, so prefer a header wherever clients can send one. Picking this is already opting into query keys, so unlike bearer tokens there's no [AuthConfig](crate::AuthConfig) switch for it, but keys are still only read on routes behind a [RequireAuthLayer](crate::RequireAuthLayer) or [BearerQueryLayer](crate::BearerQueryLayer), which make responses to them `Cache-Control: private` so shared caches don't hand them out to anyone else. The same goes for an [ApiKeyLocation::Query] from your own source.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ApiKeyQuery;
+
+impl<S> ApiKeySource<S> for ApiKeyQuery {
+    fn location(_state: &S) -> ApiKeyLocation {
+        ApiKeyLocation::Query(Cow::Borrowed("api_key"))
+    }
+}
+
+/// Source which reads API keys from the `api_key` cookie
+///
+/// This is enabled via the `auth-api-key` feature.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ApiKeyCookie;
+
+impl<S> ApiKeySource<S> for ApiKeyCookie {
+    fn location(_state: &S) -> ApiKeyLocation {
+        ApiKeyLocation::Cookie(Cow::Borrowed("api_key"))
+    }
+}
+
+impl<S> ApiKeySource<S> for ApiKeyLocation
+where
+    ApiKeyLocation: FromRef<S>,
+{
+    fn location(state: &S) -> ApiKeyLocation {
+        Self::from_ref(state)
+    }
+}
+
+/// API key extractor which contains a key read from the location its [ApiKeySource] `L` picks
+///
+/// This is enabled via the `auth-api-key` feature.
+///
+/// Keys are read from the `X-API-Key` header by default, see [ApiKeySource] for reading them from elsewhere. The second field only marks where the key came from, so it can be ignored when destructuring. The key is wrapped in a [Secret] so it's redacted when this is logged.
+///
+/// # Example
+///
+/// ```
+/// use axum::{body::Body, http::Request, routing::get, Router};
+/// use axum_auth::{ApiKeyQuery, AuthApiKey, BearerQueryLayer};
+/// use tower::ServiceExt;
+///
+/// /// Handler which takes a key from the `X-API-Key` header
+/// async fn handler(AuthApiKey(key, _): AuthApiKey) -> String {
+///     format!("Found an API key: {}", key.expose_secret())
+/// }
+///
+/// /// Handler which takes a key from the `api_key` query parameter
+/// async fn query_handler(AuthApiKey(key, _): AuthApiKey<ApiKeyQuery>) -> String {
+///     format!("Found an API key: {}", key.expose_secret())
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// // Keys are only read from the query behind a layer making responses private
+/// let app: Router = Router::new()
+///     .route("/", get(handler))
+///     .route("/query", get(query_handler))
+///     .layer(BearerQueryLayer::new());
+///
+/// let req = Request::get("/query?api_key=s3cr3t").body(Body::empty()).unwrap();
+/// let res = app.oneshot(req).await.unwrap();
+/// assert_eq!(res.headers()["Cache-Control"], "private");
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// This extractor gives off [AuthError::MissingApiKey] if there's no key, or it's in the query of a request which isn't behind a [RequireAuthLayer](crate::RequireAuthLayer) or [BearerQueryLayer](crate::BearerQueryLayer), [AuthError::MalformedApiKey] if it's empty, contains anything but visible ASCII or is given more than once, or [AuthError::TooLarge] if it's longer than the token limit set in the [AuthConfig](crate::AuthConfig), which is checked against keys as they were sent before they're decoded at all. They're sent as `401 Unauthorized` with a `WWW-Authenticate: ApiKey realm="..."` challenge, which can be changed with an [AuthConfig](crate::AuthConfig).
+pub struct AuthApiKey<L = XApiKey>(pub Secret, pub PhantomData<fn() -> L>);
+
+impl<L> fmt::Debug for AuthApiKey<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AuthApiKey").field(&self.0).finish()
+    }
+}
+
+impl<L> Clone for AuthApiKey<L> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+#[async_trait]
+impl<S, L> FromRequestParts<S> for AuthApiKey<L>
+where
+    S: Send + Sync,
+    L: ApiKeySource<S>,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Find the key where the source says it is, challenging on failure
+        let config = AuthConfig::from_parts(parts);
+        let location = L::location(state);
+        let max_len = config.get_max_token_len();
+
+        // Only read keys from the query behind a layer which makes responses to them private
+        if let ApiKeyLocation::Query(_) = location {
+            match parts.extensions.get::<QueryTokenAllowed>() {
+                Some(allowed) => allowed.mark(),
+                None => return Err(config.reject_api_key(AuthError::MissingApiKey)),
+            }
+        }
+        if location.exceeds(parts, max_len) {
+            return Err(config.reject_api_key(AuthError::TooLarge));
+        }
+        location
+            .find(parts)
+            .and_then(|key| match key.len() > max_len {
+                true => Err(AuthError::TooLarge),
+                false => Ok(Self(Secret::new(key), PhantomData)),
+            })
+            .map_err(|err| config.reject_api_key(err))
+    }
+}
+
+/// API key extractor which validates the key using a [BearerValidator] from state
+///
+/// This is enabled via the `auth-api-key` feature.
+///
+/// Keys are read the same way as [AuthApiKey] does, then handed to the validator `V`, which is taken out of your router's state using [FromRef]. Once validated, this contains the [BearerValidator::Principal] it gave back, so the same validator can be used for both bearer tokens and API keys.
+///
+/// # Example
+///
+/// ```no_run
+/// use async_trait::async_trait;
+/// use axum::{routing::get, Router};
+/// use axum_auth::{AuthError, BearerValidator, ValidatedApiKey};
+///
+/// /// Validator which knows about a single service's key
+/// #[derive(Clone)]
+/// struct Services;
+///
+/// #[async_trait]
+/// impl BearerValidator for Services {
+///     type Principal = String;
+///
+///     async fn validate(&self, key: &str) -> Result<String, AuthError> {
+///         // NOTE: compare keys in constant time outside of examples!
+///         match key {
+///             "s3cr3t" => Ok("billing".to_string()),
+///             _ => Err(AuthError::InvalidCredentials),
+///         }
+///     }
+/// }
+///
+/// /// Handler which only runs for validated services
+/// async fn handler(ValidatedApiKey(service, _): ValidatedApiKey<Services>) -> String {
+///     format!("Hello, {} service", service)
+/// }
+///
+/// let app: Router = Router::new().route("/", get(handler)).with_state(Services);
+/// ```
+///
+/// # Errors
+///
+/// On top of the errors [AuthApiKey] gives off, this gives off whatever error the validator returns, typically [AuthError::InvalidCredentials], using the same `ApiKey` challenge.
+pub struct ValidatedApiKey<V: BearerValidator, L = XApiKey>(
+    pub V::Principal,
+    pub PhantomData<fn() -> L>,
+);
+
+impl<V, L> fmt::Debug for ValidatedApiKey<V, L>
+where
+    V: BearerValidator,
+    V::Principal: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ValidatedApiKey").field(&self.0).finish()
+    }
+}
+
+impl<V, L> Clone for ValidatedApiKey<V, L>
+where
+    V: BearerValidator,
+    V::Principal: Clone,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+#[async_trait]
+impl<S, V, L> FromRequestParts<S> for ValidatedApiKey<V, L>
+where
+    S: Send + Sync,
+    V: BearerValidator + FromRef<S>,
+    L: ApiKeySource<S>,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Extract the key then hand it over to the validator
+        let AuthApiKey(key, _) = AuthApiKey::<L>::from_request_parts(parts, state).await?;
+        V::from_ref(state)
+            .validate(key.expose_secret())
+            .await
+            .map(|principal| Self(principal, PhantomData))
+            .map_err(|err| AuthConfig::from_parts(parts).reject_api_key(err))
+    }
+}
@@ -1,11 +1,35 @@
+use crate::{
+    header::{auth_header, proxy_auth_header},
+    AuthConfig, AuthError, AuthRejection, BearerValidator, Secret,
+};
 use async_trait::async_trait;
-use axum_core::extract::{FromRequestParts};
-use http::{header::AUTHORIZATION, StatusCode, request::Parts};
+use axum_core::{
+    extract::{FromRef, FromRequest, FromRequestParts},
+    response::{IntoResponse, Response},
+    BoxError,
+};
+use bytes::Bytes;
+use http::{
+    header::{CACHE_CONTROL, CONTENT_TYPE},
+    request::Parts,
+    Extensions, HeaderMap, HeaderValue, Method, Request, Uri,
+};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 /// Bearer token extractor which contains the innards of a bearer header as a string
 ///
 /// This is enabled via the `auth-bearer` feature.
-/// 
+///
+/// The token is wrapped in a [Secret] so it's redacted when this is logged, meaning it has to be [exposed](Secret::expose_secret) to be used. Everything after `Bearer ` is taken as the token by default, though its syntax can be checked by turning on [strict mode](AuthConfig::strict_bearer).
+///
+/// Tokens are only read from the `Authorization` header by default. RFC 6750 also allows them in an `access_token` query parameter, which can be turned on using [AuthConfig::bearer_query]. Responses to those mustn't be cached by anyone else, so they're only read on routes behind a [RequireAuthLayer](crate::RequireAuthLayer) or [BearerQueryLayer](crate::BearerQueryLayer), which add `Cache-Control: private` for you. Tokens in form bodies are read by [AuthBearerBody] instead.
+///
 /// # Example
 ///
 /// This structure can be used like any other [axum] extractor:
@@ -15,58 +39,392 @@ use http::{header::AUTHORIZATION, StatusCode, request::Parts};
 ///
 /// /// Handler for a typical [axum] route, takes a `token` and returns it
 /// async fn handler(AuthBearer(token): AuthBearer) -> String {
-///     format!("Found a bearer token: {}", token)
+///     format!("Found a bearer token: {}", token.expose_secret())
 /// }
 /// ```
 ///
 /// # Errors
 ///
-/// This extractor will give off a few different errors depending on what when wrong with a request's bearer token. These errors include:
+/// This extractor will give off a few different [AuthError]s depending on what when wrong with a request's bearer token. They're sent as `401 Unauthorized` with a `WWW-Authenticate: Bearer realm="..."` challenge as described in RFC 6750, which gets an `error="invalid_request"` parameter for malformed tokens. The realm and status codes can be changed with an [AuthConfig](crate::AuthConfig). These errors include:
 ///
-/// - Completely missing header, returning:
+/// - Completely missing header, giving [AuthError::MissingHeader]:
 /// ```none
-/// `Authorization\` header is missing
+/// `Authorization` header is missing
 /// ```
-/// - Header with invalid chars (i.e. non-ASCII), returning:
+/// - Header with invalid chars (i.e. non-ASCII), giving [AuthError::InvalidCharacters]:
 /// ```none
 /// `Authorization` header contains invalid characters
 /// ```
-/// - The type of authorization wasn't a bearer token, returning:
+/// - The type of authorization wasn't a bearer token, giving [AuthError::WrongScheme]:
 /// ```none
 /// `Authorization` header must be a bearer token
 /// ```
+/// - The bearer token was empty, giving [AuthError::EmptyToken]:
+/// ```none
+/// `Authorization` header's bearer token is empty
+/// ```
+/// - The bearer token didn't follow RFC 6750's syntax when [strict mode](crate::AuthConfig::strict_bearer) is on, giving [AuthError::MalformedToken]:
+/// ```none
+/// `Authorization` header's bearer token is malformed
+/// ```
+/// - The header or token were longer than the limits set in the [AuthConfig](crate::AuthConfig), giving [AuthError::TooLarge], which is checked against tokens in the query or a form as they were sent before they're decoded:
+/// ```none
+/// Credentials are too large
+/// ```
+/// - The token was sent in more than one place or more than once, giving [AuthError::DuplicateToken]:
+/// ```none
+/// Bearer token was sent more than once
+/// ```
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub struct AuthBearer(pub String);
+pub struct AuthBearer(pub Secret);
 
 #[async_trait]
 impl<S> FromRequestParts<S> for AuthBearer
 where
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, &'static str);
-
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> std::result::Result<Self, Self::Rejection> {
-        // Get authorisation header
-        let authorisation = parts
-            .headers
-            .get(AUTHORIZATION)
-            .ok_or((StatusCode::BAD_REQUEST, "`Authorization` header is missing"))?
-            .to_str()
-            .map_err(|_| {
-                (
-                    StatusCode::BAD_REQUEST,
-                    "`Authorization` header contains invalid characters",
-                )
-            })?;
-
-        // Check that its a well-formed bearer and return
-        let split = authorisation.split_once(' ');
-        match split {
-            Some((name, contents)) if name == "Bearer" => Ok(Self(contents.to_string())),
-            _ => Err((
-                StatusCode::BAD_REQUEST,
-                "`Authorization` header must be a bearer token",
-            )),
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // Check that its a well-formed bearer and return, challenging on failure
+        let config = AuthConfig::from_parts(parts);
+        bearer_from_parts(parts, &config)
+            .map(Self)
+            .map_err(|err| config.reject_bearer(err))
+    }
+}
+
+/// Bearer token extractor which also reads the token from form bodies, containing the token as well as the body
+///
+/// This is enabled via the `auth-bearer` feature.
+///
+/// This works just like [AuthBearer], but also looks for an `access_token` field in `application/x-www-form-urlencoded` bodies as RFC 6750 allows, which some webhook senders and older browsers use. Bodies of `GET` requests or of any other content type are never searched. As it reads the body, it has to be the last extractor of a handler and the body is given back as [Bytes].
+///
+/// # Example
+///
+/// ```
+/// use axum::{body::Body, extract::FromRequest, http::Request};
+/// use axum_auth::AuthBearerBody;
+///
+/// /// Handler which takes a token from either the header or the form it was posted with
+/// async fn handler(AuthBearerBody(token, form): AuthBearerBody) -> String {
+///     format!("Found a bearer token: {} in a {} byte form", token.expose_secret(), form.len())
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let req = Request::post("/")
+///     .header("Content-Type", "application/x-www-form-urlencoded")
+///     .body(Body::from("access_token=s3cr3t&event=push"))
+///     .unwrap();
+///
+/// let AuthBearerBody(token, _) = AuthBearerBody::from_request(req, &()).await.unwrap();
+/// assert_eq!(token.expose_secret(), "s3cr3t");
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// This gives off the same errors as [AuthBearer] as well as any error from reading the body. Tokens sent in the form as well as the header or query are rejected with [AuthError::DuplicateToken], as RFC 6750 only allows one of them per request.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AuthBearerBody(pub Secret, pub Bytes);
+
+#[async_trait]
+impl<S, B> FromRequest<S, B> for AuthBearerBody
+where
+    S: Send + Sync,
+    B: http_body::Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        // Keep hold of what's needed from the head then read the body
+        let config = (req.extensions().get::<AuthConfig>().cloned()).unwrap_or_default();
+        let form = is_form(req.method(), req.headers());
+        let query = query_allowed(req.extensions(), &config);
+        let (uri, headers) = (req.uri().clone(), req.headers().clone());
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        // Look for the token in the form too if it is one
+        find_bearer(&headers, &uri, &config, query, form.then_some(&body[..]))
+            .map(|token| Self(token, body))
+            .map_err(|err| config.reject_bearer(err).into_response())
+    }
+}
+
+/// Bearer token extractor for proxies, reading the token from the `Proxy-Authorization` header
+///
+/// This is enabled via the `auth-bearer` feature.
+///
+/// This works just like [AuthBearer], but is meant for forward proxies which authenticate the clients using them. Rejections are sent as `407 Proxy Authentication Required` with a `Proxy-Authenticate` challenge instead, see [AuthRejection::for_proxy].
+///
+/// # Example
+///
+/// ```no_run
+/// use axum_auth::ProxyAuthBearer;
+///
+/// /// Handler which forwards requests for authenticated clients
+/// async fn handler(ProxyAuthBearer(token): ProxyAuthBearer) -> String {
+///     format!("Forwarding with a bearer token: {}", token.expose_secret())
+/// }
+/// ```
+///
+/// # Errors
+///
+/// This gives off the same errors as [AuthBearer], which mention the `Proxy-Authorization` header when sent to clients.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ProxyAuthBearer(pub Secret);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ProxyAuthBearer
+where
+    S: Send + Sync,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // Same as a normal bearer, just with a different header and challenge
+        let config = AuthConfig::from_parts(parts);
+        parse_bearer(proxy_auth_header(&parts.headers, &config), &config)
+            .map(Self)
+            .map_err(|err| config.reject_bearer(err).for_proxy())
+    }
+}
+
+/// Marks requests whose responses are made private if they have a token in the query, which is the only time those tokens are read
+///
+/// Extractors reading other credentials from the query, like API keys, flag this so their responses are made private too.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct QueryTokenAllowed(Arc<AtomicBool>);
+
+impl QueryTokenAllowed {
+    /// Flags that credentials were read from the query
+    #[cfg(feature = "auth-api-key")]
+    pub(crate) fn mark(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Checks if credentials were read from the query by anything which flagged it
+    pub(crate) fn is_marked(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Checks if a request's bearer token can be read from the query, i.e. it's turned on and responses will be made private
+pub(crate) fn query_allowed(extensions: &Extensions, config: &AuthConfig) -> bool {
+    config.get_bearer_query() && extensions.get::<QueryTokenAllowed>().is_some()
+}
+
+/// Checks if a query string has an `access_token` parameter in it
+pub(crate) fn has_access_token(query: &str) -> bool {
+    form_urlencoded::parse(query.as_bytes()).any(|(name, _)| name == "access_token")
+}
+
+/// Marks a response as `Cache-Control: private`, dropping any `public` directive it had so shared caches never store it
+pub(crate) fn make_private(headers: &mut HeaderMap) {
+    let mut directives = vec!["private"];
+    for value in headers.get_all(CACHE_CONTROL) {
+        directives.extend(
+            (value.to_str().unwrap_or_default().split(','))
+                .map(str::trim)
+                .filter(|directive| {
+                    let name = directive.split('=').next().unwrap_or_default().trim();
+                    !name.is_empty()
+                        && !name.eq_ignore_ascii_case("public")
+                        && !name.eq_ignore_ascii_case("private")
+                }),
+        );
+    }
+    let value = HeaderValue::from_str(&directives.join(", "))
+        .unwrap_or(HeaderValue::from_static("private"));
+    headers.insert(CACHE_CONTROL, value);
+}
+
+/// Finds a bearer token in the `Authorization` header or query of a request
+pub(crate) fn bearer_from_parts(parts: &Parts, config: &AuthConfig) -> Result<Secret, AuthError> {
+    let query = query_allowed(&parts.extensions, config);
+    find_bearer(&parts.headers, &parts.uri, config, query, None)
+}
+
+/// Finds a bearer token in the `Authorization` header, or in the `query` or `form` if they're allowed, making sure it was only sent once
+fn find_bearer(
+    headers: &HeaderMap,
+    uri: &Uri,
+    config: &AuthConfig,
+    query: bool,
+    form: Option<&[u8]>,
+) -> Result<Secret, AuthError> {
+    // Find the token everywhere it's allowed to be
+    let header = auth_header(headers, config);
+    let max_len = config.get_max_token_len();
+    let query = match query {
+        true => access_token(uri.query().unwrap_or_default().as_bytes(), max_len)?,
+        false => None,
+    };
+    let form = (form.map(|form| access_token(form, max_len)))
+        .transpose()?
+        .flatten();
+
+    // Only accept one of them, falling back to the header's error if there's none
+    match (header, query, form) {
+        (Ok(("Bearer", _)), Some(_), _)
+        | (Ok(("Bearer", _)), _, Some(_))
+        | (_, Some(_), Some(_)) => Err(AuthError::DuplicateToken),
+        (_, Some(query), None) => check_bearer(&query, config),
+        (_, None, Some(form)) => check_bearer(&form, config),
+        (header, None, None) => parse_bearer(header, config),
+    }
+}
+
+/// Checks if a request has a single-part form body, which is the only kind of body RFC 6750 allows tokens in
+fn is_form(method: &Method, headers: &HeaderMap) -> bool {
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let mime = content_type.split(';').next().unwrap_or_default().trim();
+    method != Method::GET && mime.eq_ignore_ascii_case("application/x-www-form-urlencoded")
+}
+
+/// Gets the `access_token` out of a query string or form body, if it's there at most once and isn't longer than `max_len` as sent
+fn access_token(input: &[u8], max_len: usize) -> Result<Option<String>, AuthError> {
+    // Only decode the names until the token's been found and measured
+    let mut tokens = input.split(|&b| b == b'&').filter(|pair| {
+        let name = pair.split(|&b| b == b'=').next().unwrap_or_default();
+        form_urlencoded::parse(name).any(|(name, _)| name == "access_token")
+    });
+    match (tokens.next(), tokens.next()) {
+        (Some(_), Some(_)) => Err(AuthError::DuplicateToken),
+        (Some(pair), None) => {
+            let value = pair.splitn(2, |&b| b == b'=').nth(1).unwrap_or_default();
+            if value.len() > max_len {
+                return Err(AuthError::TooLarge);
+            }
+            let token = form_urlencoded::parse(pair).next();
+            Ok(token.map(|(_, token)| token.into_owned()))
         }
+        (None, _) => Ok(None),
+    }
+}
+
+/// Checks that a header split into its scheme and contents is a well-formed bearer within the configured limit, then gets the token
+pub(crate) fn parse_bearer(
+    header: Result<(&str, &str), AuthError>,
+    config: &AuthConfig,
+) -> Result<Secret, AuthError> {
+    match header? {
+        ("Bearer", contents) => check_bearer(contents, config),
+        _ => Err(AuthError::WrongScheme { expected: "Bearer" }),
+    }
+}
+
+/// Checks that a token is well-formed and within the configured limit, skipping extra spaces and tabs around it in strict mode
+fn check_bearer(token: &str, config: &AuthConfig) -> Result<Secret, AuthError> {
+    let token = match config.get_strict_bearer() {
+        true => token.trim_matches([' ', '\t']),
+        false => token,
+    };
+    if token.is_empty() {
+        Err(AuthError::EmptyToken)
+    } else if token.len() > config.get_max_token_len() {
+        Err(AuthError::TooLarge)
+    } else if config.get_strict_bearer() && !is_b64token(token) {
+        Err(AuthError::MalformedToken)
+    } else {
+        Ok(Secret::from(token))
+    }
+}
+
+/// Checks if a token follows the `b64token` syntax from RFC 6750, i.e. `1*( ALPHA / DIGIT / "-" / "." / "_" / "~" / "+" / "/" ) *"="`
+fn is_b64token(token: &str) -> bool {
+    let unpadded = token.trim_end_matches('=');
+    !unpadded.is_empty()
+        && unpadded
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-._~+/".contains(&b))
+}
+
+/// Bearer token extractor which validates the token using a [BearerValidator] from state
+///
+/// This is enabled via the `auth-bearer` feature.
+///
+/// The validator `V` is taken out of your router's state using [FromRef], so it should be cheap to clone (e.g. wrapped in an [Arc](std::sync::Arc)). Once validated, this contains the [BearerValidator::Principal] it gave back.
+///
+/// # Example
+///
+/// ```no_run
+/// use async_trait::async_trait;
+/// use axum::{routing::get, Router};
+/// use axum_auth::{AuthError, BearerValidator, ValidatedBearer};
+///
+/// /// Validator which knows about a single service's token
+/// #[derive(Clone)]
+/// struct Services;
+///
+/// #[async_trait]
+/// impl BearerValidator for Services {
+///     type Principal = String;
+///
+///     async fn validate(&self, token: &str) -> Result<String, AuthError> {
+///         match token {
+///             "s3cr3t" => Ok("billing".to_string()),
+///             _ => Err(AuthError::InvalidToken),
+///         }
+///     }
+/// }
+///
+/// /// Handler which only runs for validated services
+/// async fn handler(ValidatedBearer(service): ValidatedBearer<Services>) -> String {
+///     format!("Hello, {} service", service)
+/// }
+///
+/// let app: Router = Router::new().route("/", get(handler)).with_state(Services);
+/// ```
+///
+/// # Errors
+///
+/// On top of the errors [AuthBearer] gives off, this gives off whatever error the validator returns, typically [AuthError::InvalidToken]. This is sent with a `WWW-Authenticate: Bearer realm="...", error="invalid_token"` challenge as described in RFC 6750.
+pub struct ValidatedBearer<V: BearerValidator>(pub V::Principal);
+
+impl<V> fmt::Debug for ValidatedBearer<V>
+where
+    V: BearerValidator,
+    V::Principal: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ValidatedBearer").field(&self.0).finish()
+    }
+}
+
+impl<V> Clone for ValidatedBearer<V>
+where
+    V: BearerValidator,
+    V::Principal: Clone,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+#[async_trait]
+impl<S, V> FromRequestParts<S> for ValidatedBearer<V>
+where
+    S: Send + Sync,
+    V: BearerValidator + FromRef<S>,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Extract the token then hand it over to the validator
+        let AuthBearer(token) = AuthBearer::from_request_parts(parts, state).await?;
+        V::from_ref(state)
+            .validate(token.expose_secret())
+            .await
+            .map(Self)
+            .map_err(|err| AuthConfig::from_parts(parts).reject_bearer(err))
     }
 }
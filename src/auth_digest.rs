@@ -0,0 +1,662 @@
+use crate::{
+    header::{auth_header, auth_params},
+    AuthConfig, AuthError, AuthRejection, Challenge,
+};
+use async_trait::async_trait;
+use axum_core::{
+    extract::{FromRef, FromRequest, FromRequestParts},
+    response::{IntoResponse, Response},
+    BoxError,
+};
+use bytes::Bytes;
+use hmac::{Hmac, Mac};
+use http::{request::Parts, HeaderMap, Method, Request, Uri};
+use md5::Md5;
+use sha2::{Digest, Sha256, Sha512_256};
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use subtle::ConstantTimeEq;
+
+/// Number of tracked nonces after which expired ones start getting swept out whenever a new one is used
+const SWEEP_THRESHOLD: usize = 1024;
+
+/// Maximum number of tracked nonces, past which the oldest one is forgotten and any nonce issued before it becomes stale
+const MAX_NONCES: usize = 65536;
+
+/// Hash algorithm used for digest authentication as described in RFC 7616
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DigestAlgorithm {
+    /// `MD5`, which is insecure but the only one supported by many older clients
+    Md5,
+    /// `MD5-sess`
+    Md5Sess,
+    /// `SHA-256`
+    Sha256,
+    /// `SHA-256-sess`
+    Sha256Sess,
+    /// `SHA-512-256`
+    Sha512_256,
+    /// `SHA-512-256-sess`
+    Sha512_256Sess,
+}
+
+impl DigestAlgorithm {
+    /// Name of this algorithm as sent in headers, e.g. `SHA-256`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Md5Sess => "MD5-sess",
+            Self::Sha256 => "SHA-256",
+            Self::Sha256Sess => "SHA-256-sess",
+            Self::Sha512_256 => "SHA-512-256",
+            Self::Sha512_256Sess => "SHA-512-256-sess",
+        }
+    }
+
+    /// Checks if this is a session variant, where the client's nonce is mixed into the first hash
+    pub fn is_sess(&self) -> bool {
+        matches!(
+            self,
+            Self::Md5Sess | Self::Sha256Sess | Self::Sha512_256Sess
+        )
+    }
+
+    /// Hashes `data` using this algorithm, returning it as lowercase hex
+    pub fn hash(&self, data: &[u8]) -> String {
+        match self {
+            Self::Md5 | Self::Md5Sess => hex(&Md5::digest(data)),
+            Self::Sha256 | Self::Sha256Sess => hex(&Sha256::digest(data)),
+            Self::Sha512_256 | Self::Sha512_256Sess => hex(&Sha512_256::digest(data)),
+        }
+    }
+
+    /// Gets the plain variant of this algorithm, without `-sess`
+    fn base(&self) -> Self {
+        match self {
+            Self::Md5 | Self::Md5Sess => Self::Md5,
+            Self::Sha256 | Self::Sha256Sess => Self::Sha256,
+            Self::Sha512_256 | Self::Sha512_256Sess => Self::Sha512_256,
+        }
+    }
+}
+
+impl FromStr for DigestAlgorithm {
+    type Err = AuthError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Self::Md5,
+            Self::Md5Sess,
+            Self::Sha256,
+            Self::Sha256Sess,
+            Self::Sha512_256,
+            Self::Sha512_256Sess,
+        ]
+        .into_iter()
+        .find(|alg| alg.name().eq_ignore_ascii_case(s))
+        .ok_or(AuthError::InvalidParameters)
+    }
+}
+
+impl fmt::Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Quality of protection used for digest authentication
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Qop {
+    /// `auth`, which only protects the request's method and URI
+    Auth,
+    /// `auth-int`, which also protects the request's body and needs [AuthDigestBody] to be used
+    AuthInt,
+}
+
+impl Qop {
+    /// Name of this quality of protection as sent in headers, e.g. `auth-int`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::AuthInt => "auth-int",
+        }
+    }
+}
+
+/// Secret for a user which their digest response can be checked against
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum DigestSecret {
+    /// Plaintext password of the user, which works with every algorithm
+    Password(String),
+    /// Pre-computed hash of `username:realm:password` in lowercase hex, which only works with algorithms using the same underlying hash
+    ///
+    /// This is what `htdigest` files contain for `MD5`.
+    Ha1 {
+        /// Algorithm this was hashed using, either the plain or `-sess` variant
+        algorithm: DigestAlgorithm,
+        /// The hash itself
+        hash: String,
+    },
+}
+
+/// Store of user secrets for digest authentication
+///
+/// This is implemented for `HashMap<String, String>` of usernames to plaintext passwords for convenience.
+#[async_trait]
+pub trait DigestCredentials: Send + Sync {
+    /// Gets the secret of a user in the given realm, or [None] if they don't exist
+    async fn secret(&self, username: &str, realm: &str) -> Option<DigestSecret>;
+}
+
+#[async_trait]
+impl DigestCredentials for HashMap<String, String> {
+    async fn secret(&self, username: &str, _realm: &str) -> Option<DigestSecret> {
+        self.get(username).cloned().map(DigestSecret::Password)
+    }
+}
+
+#[async_trait]
+impl<C> DigestCredentials for Arc<C>
+where
+    C: DigestCredentials + ?Sized,
+{
+    async fn secret(&self, username: &str, realm: &str) -> Option<DigestSecret> {
+        (**self).secret(username, realm).await
+    }
+}
+
+/// Authenticator for digest authentication as described in RFC 7616, containing user credentials and issued nonces
+///
+/// This is enabled via the `auth-digest` feature.
+///
+/// This is what the [AuthDigest] and [AuthDigestBody] extractors take out of your router's state using [FromRef]. It offers `SHA-256` and `MD5` with `qop=auth` by default, and nonces last for five minutes before clients are asked to retry with a new one using `stale=true`. Nonce counts (`nc`) have to go up with every request using the same nonce, so replayed requests are rejected.
+///
+/// Nonces are signed timestamps rather than being stored when issued, so clients which never authenticate don't use up any memory. A nonce's count only starts being tracked once it's been used successfully.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{routing::get, Router};
+/// use axum_auth::{AuthDigest, DigestAuth};
+/// use std::collections::HashMap;
+///
+/// /// Handler which greets the authenticated user
+/// async fn handler(AuthDigest(username): AuthDigest) -> String {
+///     format!("Hello, {}", username)
+/// }
+///
+/// let users = HashMap::from([("admin".to_string(), "hunter2".to_string())]);
+/// let app: Router = Router::new()
+///     .route("/", get(handler))
+///     .with_state(DigestAuth::new(users));
+/// ```
+#[derive(Clone)]
+pub struct DigestAuth {
+    credentials: Arc<dyn DigestCredentials>,
+    algorithms: Arc<[DigestAlgorithm]>,
+    qops: Arc<[Qop]>,
+    nonce_lifetime: Duration,
+    opaque: Arc<str>,
+    nonce_key: Hmac<Sha256>,
+    epoch: Instant,
+    nonces: Arc<Mutex<NonceCounts>>,
+}
+
+/// Counts of nonces which have been used, keyed by nonce
+#[derive(Default)]
+struct NonceCounts {
+    counts: HashMap<String, NonceState>,
+    /// Nonces issued at or before this which aren't tracked have been forgotten, so are stale
+    forgotten: Option<u64>,
+}
+
+/// Information about a used nonce
+struct NonceState {
+    issued: u64,
+    nc: u32,
+}
+
+impl DigestAuth {
+    /// Creates a new authenticator which gets secrets from `credentials`
+    pub fn new(credentials: impl DigestCredentials + 'static) -> Self {
+        Self {
+            credentials: Arc::new(credentials),
+            algorithms: Arc::new([DigestAlgorithm::Sha256, DigestAlgorithm::Md5]),
+            qops: Arc::new([Qop::Auth]),
+            nonce_lifetime: Duration::from_secs(5 * 60),
+            opaque: random_hex().into(),
+            nonce_key: Hmac::new_from_slice(&random_bytes::<32>())
+                .expect("HMAC takes keys of any length"),
+            epoch: Instant::now(),
+            nonces: Arc::default(),
+        }
+    }
+
+    /// Sets the algorithms which are accepted, in order of preference
+    ///
+    /// A challenge is sent for every one of these, as clients pick the first one they support.
+    pub fn algorithms(mut self, algorithms: &[DigestAlgorithm]) -> Self {
+        self.algorithms = algorithms.into();
+        self
+    }
+
+    /// Sets the qualities of protection which are accepted
+    ///
+    /// Accepting [Qop::AuthInt] requires using the [AuthDigestBody] extractor, as [AuthDigest] can't read the body.
+    pub fn qop(mut self, qops: &[Qop]) -> Self {
+        self.qops = qops.into();
+        self
+    }
+
+    /// Sets how long nonces can be used for before clients have to get a new one
+    pub fn nonce_lifetime(mut self, lifetime: Duration) -> Self {
+        self.nonce_lifetime = lifetime;
+        self
+    }
+
+    /// Authenticates a request, giving back the username on success or a rejection with fresh challenges on failure
+    async fn authenticate(
+        &self,
+        config: &AuthConfig,
+        method: &Method,
+        uri: &Uri,
+        headers: &HeaderMap,
+        body: Option<&[u8]>,
+    ) -> Result<String, AuthRejection> {
+        self.check(config, method, uri, headers, body)
+            .await
+            .map_err(|err| {
+                let challenges = self.challenges(config, err == AuthError::StaleNonce);
+                config.reject(err, challenges)
+            })
+    }
+
+    /// Checks a request's digest credentials, giving back the username if they're valid
+    async fn check(
+        &self,
+        config: &AuthConfig,
+        method: &Method,
+        uri: &Uri,
+        headers: &HeaderMap,
+        body: Option<&[u8]>,
+    ) -> Result<String, AuthError> {
+        // Get all parameters
+        let params = match auth_header(headers, config)? {
+            ("Digest", contents) => auth_params(contents).ok_or(AuthError::InvalidParameters)?,
+            _ => return Err(AuthError::WrongScheme { expected: "Digest" }),
+        };
+        let param = |name| params.get(name).ok_or(AuthError::InvalidParameters);
+        let username = match (params.get("username"), params.get("username*")) {
+            (Some(username), None) => username.clone(),
+            (None, Some(encoded)) => {
+                decode_ext_value(encoded).ok_or(AuthError::InvalidParameters)?
+            }
+            _ => return Err(AuthError::InvalidParameters),
+        };
+        if username.len() > config.get_max_username_len() {
+            return Err(AuthError::TooLarge);
+        }
+        let (nonce, digest_uri, response) = (param("nonce")?, param("uri")?, param("response")?);
+        let (cnonce, nc) = (param("cnonce")?, param("nc")?);
+        let algorithm = match params.get("algorithm") {
+            Some(algorithm) => algorithm.parse()?,
+            None => DigestAlgorithm::Md5,
+        };
+        let qop = match param("qop")?.as_str() {
+            "auth" => Qop::Auth,
+            "auth-int" => Qop::AuthInt,
+            _ => return Err(AuthError::InvalidParameters),
+        };
+        let nc = match nc.len() {
+            8 => u32::from_str_radix(nc, 16).map_err(|_| AuthError::InvalidParameters)?,
+            _ => return Err(AuthError::InvalidParameters),
+        };
+
+        // Make sure it's for this request and uses options we accept
+        let target = uri.path_and_query().map_or("/", |target| target.as_str());
+        if digest_uri != target && *digest_uri != uri.to_string() {
+            return Err(AuthError::InvalidParameters);
+        }
+        if !self.algorithms.contains(&algorithm) || !self.qops.contains(&qop) {
+            return Err(AuthError::InvalidParameters);
+        }
+        if params
+            .get("userhash")
+            .is_some_and(|userhash| userhash == "true")
+        {
+            return Err(AuthError::InvalidParameters);
+        }
+        let body_hash = match (qop, body) {
+            (Qop::Auth, _) => None,
+            (Qop::AuthInt, Some(body)) => Some(algorithm.hash(body)),
+            (Qop::AuthInt, None) => return Err(AuthError::InvalidParameters),
+        };
+        if param("realm")? != config.get_realm()
+            || params
+                .get("opaque")
+                .is_some_and(|opaque| **opaque != *self.opaque)
+        {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        // Work out what the response should be for the user's secret, going through the motions with an empty password for unknown users so they take as long as wrong passwords
+        let secret = self.credentials.secret(&username, config.get_realm()).await;
+        let (known, ha1) = match secret {
+            Some(DigestSecret::Password(password)) => (
+                true,
+                algorithm
+                    .hash(format!("{}:{}:{}", username, config.get_realm(), password).as_bytes()),
+            ),
+            Some(DigestSecret::Ha1 {
+                algorithm: hashed,
+                hash,
+            }) if hashed.base() == algorithm.base() => (true, hash.to_ascii_lowercase()),
+            _ => (
+                false,
+                algorithm.hash(format!("{}:{}:", username, config.get_realm()).as_bytes()),
+            ),
+        };
+        let ha1 = match algorithm.is_sess() {
+            true => algorithm.hash(format!("{}:{}:{}", ha1, nonce, cnonce).as_bytes()),
+            false => ha1,
+        };
+        let ha2 = match body_hash {
+            Some(body_hash) => {
+                algorithm.hash(format!("{}:{}:{}", method, digest_uri, body_hash).as_bytes())
+            }
+            None => algorithm.hash(format!("{}:{}", method, digest_uri).as_bytes()),
+        };
+        let expected = algorithm.hash(
+            format!(
+                "{}:{}:{:08x}:{}:{}:{}",
+                ha1,
+                nonce,
+                nc,
+                cnonce,
+                qop.name(),
+                ha2
+            )
+            .as_bytes(),
+        );
+        let matches = expected
+            .as_bytes()
+            .ct_eq(response.to_ascii_lowercase().as_bytes());
+        if !(known && bool::from(matches)) {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        // Only accept nonces we've issued which are still fresh and haven't seen this count yet
+        self.use_nonce(nonce, nc)?;
+        Ok(username)
+    }
+
+    /// Records a use of a nonce with a count, making sure it's one of ours, it's fresh and the count has gone up
+    fn use_nonce(&self, nonce: &str, nc: u32) -> Result<(), AuthError> {
+        let issued = self.nonce_issued(nonce).ok_or(AuthError::StaleNonce)?;
+        let now = self.now();
+        let lifetime = self.nonce_lifetime.as_millis() as u64;
+        if now.saturating_sub(issued) >= lifetime || nc == 0 {
+            return Err(AuthError::StaleNonce);
+        }
+
+        let mut nonces = self.nonces.lock().unwrap();
+        if let Some(state) = nonces.counts.get_mut(nonce) {
+            return match nc > state.nc {
+                true => {
+                    state.nc = nc;
+                    Ok(())
+                }
+                false => Err(AuthError::StaleNonce),
+            };
+        }
+        if nonces
+            .forgotten
+            .is_some_and(|forgotten| issued <= forgotten)
+        {
+            return Err(AuthError::StaleNonce);
+        }
+
+        // Start tracking it, forgetting the oldest nonce if there's too many
+        if nonces.counts.len() >= SWEEP_THRESHOLD {
+            nonces
+                .counts
+                .retain(|_, state| now.saturating_sub(state.issued) < lifetime);
+        }
+        if nonces.counts.len() >= MAX_NONCES {
+            let oldest = nonces
+                .counts
+                .iter()
+                .min_by_key(|(_, state)| state.issued)
+                .map(|(nonce, state)| (nonce.clone(), state.issued));
+            if let Some((oldest, issued)) = oldest {
+                nonces.counts.remove(&oldest);
+                nonces.forgotten = nonces.forgotten.max(Some(issued));
+            }
+        }
+        nonces
+            .counts
+            .insert(nonce.to_string(), NonceState { issued, nc });
+        Ok(())
+    }
+
+    /// Creates challenges for every accepted algorithm using a newly-issued nonce
+    fn challenges(&self, config: &AuthConfig, stale: bool) -> Vec<Challenge> {
+        let nonce = self.issue_nonce();
+        let qop = (self.qops.iter())
+            .map(Qop::name)
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.algorithms
+            .iter()
+            .map(|algorithm| {
+                let challenge = Challenge::new("Digest")
+                    .param("realm", config.get_realm())
+                    .param("qop", qop.clone())
+                    .token_param("algorithm", algorithm.name())
+                    .param("nonce", nonce.clone())
+                    .param("opaque", &*self.opaque);
+                match stale {
+                    true => challenge.token_param("stale", "true"),
+                    false => challenge,
+                }
+            })
+            .collect()
+    }
+
+    /// Issues a new nonce, made up of when it was issued and some random bytes followed by a signature of them
+    fn issue_nonce(&self) -> String {
+        let mut data = [0; 16];
+        data[..8].copy_from_slice(&self.now().to_be_bytes());
+        data[8..].copy_from_slice(&random_bytes::<8>());
+        format!("{}{}", hex(&data), hex(&self.sign_nonce(&data)))
+    }
+
+    /// Checks the signature of a nonce, giving back when it was issued if it's one of ours
+    fn nonce_issued(&self, nonce: &str) -> Option<u64> {
+        if nonce.len() != 64 {
+            return None;
+        }
+        let bytes = (0..32)
+            .map(|i| u8::from_str_radix(nonce.get(i * 2..i * 2 + 2)?, 16).ok())
+            .collect::<Option<Vec<_>>>()?;
+        let (data, signature) = bytes.split_at(16);
+        let valid = self.sign_nonce(data).ct_eq(signature);
+        bool::from(valid).then(|| u64::from_be_bytes(data[..8].try_into().unwrap()))
+    }
+
+    /// Signs a nonce's data, truncating the signature to 16 bytes
+    fn sign_nonce(&self, data: &[u8]) -> [u8; 16] {
+        let mut mac = self.nonce_key.clone();
+        mac.update(data);
+        let mut signature = [0; 16];
+        signature.copy_from_slice(&mac.finalize().into_bytes()[..16]);
+        signature
+    }
+
+    /// Gets the number of milliseconds since this authenticator was created, which nonces are timestamped with
+    fn now(&self) -> u64 {
+        self.epoch.elapsed().as_millis() as u64
+    }
+}
+
+impl fmt::Debug for DigestAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DigestAuth")
+            .field("algorithms", &self.algorithms)
+            .field("qops", &self.qops)
+            .field("nonce_lifetime", &self.nonce_lifetime)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Digest authentication extractor which contains the username of the authenticated user
+///
+/// This is enabled via the `auth-digest` feature.
+///
+/// Credentials are checked using a [DigestAuth] taken out of your router's state using [FromRef]. As this doesn't read the request body, it only accepts `qop=auth` and [AuthDigestBody] has to be used instead for `qop=auth-int`.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{routing::get, Router};
+/// use axum_auth::{AuthDigest, DigestAlgorithm, DigestAuth};
+/// use std::collections::HashMap;
+///
+/// /// Handler which greets the authenticated user
+/// async fn handler(AuthDigest(username): AuthDigest) -> String {
+///     format!("Hello, {}", username)
+/// }
+///
+/// let users = HashMap::from([("admin".to_string(), "hunter2".to_string())]);
+/// let digest = DigestAuth::new(users).algorithms(&[DigestAlgorithm::Sha512_256]);
+///
+/// let app: Router = Router::new().route("/", get(handler)).with_state(digest);
+/// ```
+///
+/// # Errors
+///
+/// On top of [AuthError::MissingHeader], [AuthError::InvalidCharacters] and [AuthError::WrongScheme], this gives off [AuthError::InvalidParameters] for malformed digests, [AuthError::InvalidCredentials] for wrong usernames or passwords, [AuthError::StaleNonce] for nonces which have expired or have been replayed, and [AuthError::TooLarge] for headers or usernames over the limits set in the [AuthConfig]. Every error is sent with `WWW-Authenticate: Digest` challenges containing a fresh nonce, with `stale=true` added for stale nonces so clients retry without asking the user again.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AuthDigest(pub String);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthDigest
+where
+    S: Send + Sync,
+    DigestAuth: FromRef<S>,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let config = AuthConfig::from_parts(parts);
+        DigestAuth::from_ref(state)
+            .authenticate(&config, &parts.method, &parts.uri, &parts.headers, None)
+            .await
+            .map(Self)
+    }
+}
+
+/// Digest authentication extractor which reads the request body, containing the username of the authenticated user and the body
+///
+/// This is enabled via the `auth-digest` feature.
+///
+/// This works just like [AuthDigest] but also accepts `qop=auth-int`, which protects the request body too. As it reads the body, it has to be the last extractor of a handler and the body is given back as [Bytes].
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{routing::post, Router};
+/// use axum_auth::{AuthDigestBody, DigestAuth, Qop};
+/// use std::collections::HashMap;
+///
+/// /// Handler which echoes back the body it was sent
+/// async fn handler(AuthDigestBody(username, body): AuthDigestBody) -> String {
+///     format!("{} sent {} bytes", username, body.len())
+/// }
+///
+/// let users = HashMap::from([("admin".to_string(), "hunter2".to_string())]);
+/// let digest = DigestAuth::new(users).qop(&[Qop::AuthInt, Qop::Auth]);
+///
+/// let app: Router = Router::new().route("/", post(handler)).with_state(digest);
+/// ```
+///
+/// # Errors
+///
+/// This gives off the same errors as [AuthDigest] as well as any error from reading the body.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AuthDigestBody(pub String, pub Bytes);
+
+#[async_trait]
+impl<S, B> FromRequest<S, B> for AuthDigestBody
+where
+    S: Send + Sync,
+    B: http_body::Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    DigestAuth: FromRef<S>,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        // Keep hold of what's needed from the head then read the body
+        let config = (req.extensions().get::<AuthConfig>().cloned()).unwrap_or_default();
+        let (method, uri, headers) = (
+            req.method().clone(),
+            req.uri().clone(),
+            req.headers().clone(),
+        );
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        // Authenticate with the body
+        DigestAuth::from_ref(state)
+            .authenticate(&config, &method, &uri, &headers, Some(&body))
+            .await
+            .map(|username| Self(username, body))
+            .map_err(IntoResponse::into_response)
+    }
+}
+
+/// Decodes an extended parameter value as described in RFC 8187, e.g. `UTF-8''J%C3%BCrgen`
+fn decode_ext_value(input: &str) -> Option<String> {
+    let (charset, rest) = input.split_once('\'')?;
+    let (_, encoded) = rest.split_once('\'')?;
+    if !charset.eq_ignore_ascii_case("UTF-8") {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut iter = encoded.bytes();
+    while let Some(byte) = iter.next() {
+        bytes.push(match byte {
+            b'%' => {
+                let hex = [iter.next()?, iter.next()?];
+                u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?
+            }
+            byte => byte,
+        });
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Generates 16 random bytes encoded as lowercase hex
+fn random_hex() -> String {
+    hex(&random_bytes::<16>())
+}
+
+/// Generates `N` random bytes
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0; N];
+    getrandom::getrandom(&mut bytes).expect("Couldn't generate random bytes");
+    bytes
+}
+
+/// Encodes bytes as lowercase hex
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,216 @@
+#[cfg(feature = "auth-jwks")]
+use crate::JwkKeySet;
+use crate::{AuthBearer, AuthConfig, AuthError, AuthRejection};
+use async_trait::async_trait;
+use axum_core::extract::{FromRef, FromRequestParts};
+use http::request::Parts;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::de::DeserializeOwned;
+use std::{fmt, sync::Arc};
+
+/// Decoder for JSON Web Tokens, containing the key and validation rules used by [AuthJwt]
+///
+/// This is enabled via the `auth-jwt` feature.
+///
+/// By default, the signature as well as the `exp` and `nbf` claims are checked with a leeway of 60 seconds. Issuers and audiences are only checked once they've been set.
+///
+/// # Example
+///
+/// ```
+/// use axum_auth::{jsonwebtoken::{Algorithm, DecodingKey}, JwtDecoder};
+///
+/// let decoder = JwtDecoder::new(Algorithm::HS256, DecodingKey::from_secret(b"s3cr3t"))
+///     .issuer(&["https://auth.example.com"])
+///     .audience(&["my-api"])
+///     .leeway(30);
+/// ```
+#[derive(Clone)]
+pub struct JwtDecoder {
+    inner: Arc<JwtInner>,
+}
+
+#[derive(Clone)]
+struct JwtInner {
+    keys: JwtKeys,
+    validation: Validation,
+}
+
+/// Keys which tokens can be signed with
+#[derive(Clone)]
+enum JwtKeys {
+    Single(DecodingKey),
+    #[cfg(feature = "auth-jwks")]
+    Set(JwkKeySet),
+}
+
+impl JwtDecoder {
+    /// Creates a new decoder which verifies signatures made using `algorithm` with a `key`
+    ///
+    /// Supported algorithms include the HMAC ones (`HS256`, `HS384` and `HS512`), which take a secret, as well as `RS256`, `ES256` and `EdDSA` amongst others, which take a public key.
+    pub fn new(algorithm: Algorithm, key: DecodingKey) -> Self {
+        Self::with_keys(algorithm, JwtKeys::Single(key))
+    }
+
+    /// Creates a new decoder which picks keys out of a [JwkKeySet] using the `kid` header of tokens
+    ///
+    /// Tokens may be signed using any algorithm which the key they're signed with allows, going by the key's `alg` or otherwise its type.
+    ///
+    /// This is enabled via the `auth-jwks` feature.
+    #[cfg(feature = "auth-jwks")]
+    pub fn from_jwks(keys: JwkKeySet) -> Self {
+        Self::with_keys(Algorithm::RS256, JwtKeys::Set(keys))
+    }
+
+    fn with_keys(algorithm: Algorithm, keys: JwtKeys) -> Self {
+        let mut validation = Validation::new(algorithm);
+        validation.validate_nbf = true;
+        validation.validate_aud = false;
+        Self {
+            inner: Arc::new(JwtInner { keys, validation }),
+        }
+    }
+
+    /// Only accepts tokens with an `iss` claim matching one of these issuers
+    pub fn issuer<T: ToString>(mut self, issuers: &[T]) -> Self {
+        let validation = self.validation();
+        validation.set_issuer(issuers);
+        validation.required_spec_claims.insert("iss".to_string());
+        self
+    }
+
+    /// Only accepts tokens with an `aud` claim matching one of these audiences
+    pub fn audience<T: ToString>(mut self, audiences: &[T]) -> Self {
+        let validation = self.validation();
+        validation.set_audience(audiences);
+        validation.validate_aud = true;
+        validation.required_spec_claims.insert("aud".to_string());
+        self
+    }
+
+    /// Sets the leeway in seconds given to the `exp` and `nbf` claims to allow for clock skew
+    pub fn leeway(mut self, seconds: u64) -> Self {
+        self.validation().leeway = seconds;
+        self
+    }
+
+    /// Requires the given claims to be present, on top of the always-required `exp` claim and any `iss` or `aud` claims being checked
+    pub fn required_claims<T: ToString>(mut self, claims: &[T]) -> Self {
+        let validation = self.validation();
+        validation.set_required_spec_claims(claims);
+        validation.required_spec_claims.insert("exp".to_string());
+        if validation.iss.is_some() {
+            validation.required_spec_claims.insert("iss".to_string());
+        }
+        if validation.validate_aud {
+            validation.required_spec_claims.insert("aud".to_string());
+        }
+        self
+    }
+
+    /// Decodes and validates a `token`, returning its claims
+    ///
+    /// Tokens which fail validation for any reason give [AuthError::InvalidToken].
+    pub async fn decode<C: DeserializeOwned>(&self, token: &str) -> Result<C, AuthError> {
+        let decode = |key, validation| {
+            jsonwebtoken::decode(token, key, validation)
+                .map(|data| data.claims)
+                .map_err(|_| AuthError::InvalidToken)
+        };
+        match &self.inner.keys {
+            JwtKeys::Single(key) => decode(key, &self.inner.validation),
+            #[cfg(feature = "auth-jwks")]
+            JwtKeys::Set(keys) => {
+                // Find the key it was signed with then only allow that key's algorithm
+                let header =
+                    jsonwebtoken::decode_header(token).map_err(|_| AuthError::InvalidToken)?;
+                let key = keys.find(header.kid.as_deref(), header.alg).await?;
+                let mut validation = self.inner.validation.clone();
+                validation.algorithms = vec![header.alg];
+                decode(&key, &validation)
+            }
+        }
+    }
+
+    /// Gets the validation rules for changing, cloning them if this decoder is shared
+    fn validation(&mut self) -> &mut Validation {
+        &mut Arc::make_mut(&mut self.inner).validation
+    }
+}
+
+impl fmt::Debug for JwtDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtDecoder")
+            .field("validation", &self.inner.validation)
+            .finish_non_exhaustive()
+    }
+}
+
+/// JSON Web Token extractor which validates a bearer token and contains its typed claims
+///
+/// This is enabled via the `auth-jwt` feature.
+///
+/// Tokens are validated using a [JwtDecoder] taken out of your router's state using [FromRef], and their claims are deserialized into `C`.
+///
+/// # Example
+///
+/// ```
+/// use axum::{extract::FromRequestParts, http::Request, routing::get, Router};
+/// use axum_auth::{jsonwebtoken::{self, Algorithm, DecodingKey, EncodingKey, Header}, AuthJwt, JwtDecoder};
+/// use serde::{Deserialize, Serialize};
+///
+/// /// Claims we expect our tokens to have
+/// #[derive(Serialize, Deserialize)]
+/// struct Claims {
+///     sub: String,
+///     exp: u64,
+/// }
+///
+/// /// Handler which greets the token's subject
+/// async fn handler(AuthJwt(claims): AuthJwt<Claims>) -> String {
+///     format!("Hello, {}", claims.sub)
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let decoder = JwtDecoder::new(Algorithm::HS256, DecodingKey::from_secret(b"s3cr3t"));
+/// let app: Router = Router::new().route("/", get(handler)).with_state(decoder.clone());
+///
+/// // Sign a token and check it can be extracted
+/// let claims = Claims { sub: "ferris".to_string(), exp: 4102444800 };
+/// let token = jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(b"s3cr3t")).unwrap();
+/// let (mut parts, _) = Request::builder()
+///     .header("Authorization", format!("Bearer {}", token))
+///     .body(())
+///     .unwrap()
+///     .into_parts();
+///
+/// let AuthJwt(claims) = AuthJwt::<Claims>::from_request_parts(&mut parts, &decoder).await.unwrap();
+/// assert_eq!(claims.sub, "ferris");
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// On top of the errors [AuthBearer] gives off, this gives off [AuthError::InvalidToken] if the token's signature or claims fail validation, or its claims couldn't be deserialized. If the decoder uses a [JwkKeySet](crate::JwkKeySet) which couldn't be loaded, this gives off [AuthError::Unavailable] instead. This is sent with a `WWW-Authenticate: Bearer realm="...", error="invalid_token"` challenge as described in RFC 6750.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AuthJwt<C>(pub C);
+
+#[async_trait]
+impl<S, C> FromRequestParts<S> for AuthJwt<C>
+where
+    S: Send + Sync,
+    C: DeserializeOwned,
+    JwtDecoder: FromRef<S>,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Extract the token then decode it into claims
+        let AuthBearer(token) = AuthBearer::from_request_parts(parts, state).await?;
+        JwtDecoder::from_ref(state)
+            .decode(token.expose_secret())
+            .await
+            .map(Self)
+            .map_err(|err| AuthConfig::from_parts(parts).reject_bearer(err))
+    }
+}
@@ -0,0 +1,328 @@
+use crate::AuthError;
+#[cfg(feature = "auth-basic")]
+use crate::BasicCharset;
+#[cfg(any(
+    feature = "auth-basic",
+    feature = "auth-bearer",
+    feature = "auth-digest",
+    feature = "auth-session"
+))]
+use crate::{AuthRejection, Challenge};
+use http::{request::Parts, StatusCode};
+use std::borrow::Cow;
+
+/// Configuration for how the extractors in this crate reject requests
+///
+/// The extractors look for this inside of the request's extensions, so the easiest way to set it for a whole app is with axum's `Extension` layer. If it's missing, the [default](AuthConfig::default) is used instead.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{http::StatusCode, routing::get, Extension, Router};
+/// use axum_auth::{AuthBasic, AuthConfig, AuthError};
+///
+/// async fn handler(AuthBasic((id, _)): AuthBasic) -> String {
+///     format!("Hello, {}", id)
+/// }
+///
+/// let config = AuthConfig::new()
+///     .realm("admin panel")
+///     .status_mapping(|err| match err {
+///         AuthError::MissingHeader => StatusCode::UNAUTHORIZED,
+///         _ => StatusCode::BAD_REQUEST,
+///     });
+///
+/// let app: Router = Router::new()
+///     .route("/", get(handler))
+///     .layer(Extension(config));
+/// ```
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    realm: Cow<'static, str>,
+    status: fn(&AuthError) -> StatusCode,
+    #[cfg(feature = "auth-basic")]
+    basic_charset: BasicCharset,
+    max_header_len: usize,
+    max_basic_len: usize,
+    max_username_len: usize,
+    max_password_len: usize,
+    max_token_len: usize,
+    case_insensitive_schemes: bool,
+    #[cfg(feature = "auth-bearer")]
+    strict_bearer: bool,
+    #[cfg(feature = "auth-bearer")]
+    bearer_query: bool,
+}
+
+impl AuthConfig {
+    /// Creates a new default configuration, see [AuthConfig::default] for details
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the realm sent in challenges, which browsers may show to users in their login prompt
+    pub fn realm(mut self, realm: impl Into<Cow<'static, str>>) -> Self {
+        self.realm = realm.into();
+        self
+    }
+
+    /// Sets the function deciding which status code each [AuthError] is sent with
+    pub fn status_mapping(mut self, status: fn(&AuthError) -> StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Sets the character set basic authentication credentials are decoded with, defaulting to strict UTF-8
+    #[cfg(feature = "auth-basic")]
+    pub fn basic_charset(mut self, charset: BasicCharset) -> Self {
+        self.basic_charset = charset;
+        self
+    }
+
+    /// Sets the maximum length in bytes of an `Authorization` or `Proxy-Authorization` header, defaulting to 8 KiB
+    ///
+    /// Longer headers are rejected with [AuthError::TooLarge] before they're parsed at all. Other headers aren't limited, so make sure your web server's limits are set properly too.
+    pub fn max_header_len(mut self, len: usize) -> Self {
+        self.max_header_len = len;
+        self
+    }
+
+    /// Sets the maximum length in bytes of decoded basic authentication credentials, defaulting to 4 KiB
+    ///
+    /// Credentials which would decode to more than this are rejected with [AuthError::TooLarge] before any base64 is decoded.
+    pub fn max_basic_len(mut self, len: usize) -> Self {
+        self.max_basic_len = len;
+        self
+    }
+
+    /// Sets the maximum length in bytes of basic authentication identifiers and digest usernames, defaulting to 1 KiB
+    pub fn max_username_len(mut self, len: usize) -> Self {
+        self.max_username_len = len;
+        self
+    }
+
+    /// Sets the maximum length in bytes of basic authentication passwords, defaulting to 1 KiB
+    pub fn max_password_len(mut self, len: usize) -> Self {
+        self.max_password_len = len;
+        self
+    }
+
+    /// Sets the maximum length in bytes of bearer tokens and API keys, defaulting to 8 KiB
+    pub fn max_token_len(mut self, len: usize) -> Self {
+        self.max_token_len = len;
+        self
+    }
+
+    /// Sets whether schemes such as `bearer` are matched regardless of case as RFC 9110 requires, which is on by default
+    ///
+    /// Turning this off only accepts schemes spelt exactly like `Basic`, `Bearer` and `Digest`, which is how this crate used to behave.
+    pub fn case_insensitive_schemes(mut self, enabled: bool) -> Self {
+        self.case_insensitive_schemes = enabled;
+        self
+    }
+
+    /// Sets whether bearer tokens are checked against the `b64token` syntax from RFC 6750, which is off by default
+    ///
+    /// In strict mode, extra spaces and tabs around tokens are skipped and tokens containing anything but letters, digits, `-._~+/` and trailing `=` padding are rejected with [AuthError::MalformedToken]. Otherwise, everything after `Bearer ` is taken as the token as-is.
+    #[cfg(feature = "auth-bearer")]
+    pub fn strict_bearer(mut self, enabled: bool) -> Self {
+        self.strict_bearer = enabled;
+        self
+    }
+
+    /// Sets whether bearer tokens are also read from an `access_token` query parameter as RFC 6750 allows, which is off by default
+    ///
+    /// URLs tend to end up in logs and browser histories, so only turn this on for clients which can't send headers. Requests sending a token in both the query and the `Authorization` header are rejected with [AuthError::DuplicateToken]. Query tokens are still only read on routes behind a [RequireAuthLayer](crate::RequireAuthLayer) or [BearerQueryLayer](crate::BearerQueryLayer), which make responses to them private.
+    #[cfg(feature = "auth-bearer")]
+    pub fn bearer_query(mut self, enabled: bool) -> Self {
+        self.bearer_query = enabled;
+        self
+    }
+
+    /// Gets the realm sent in challenges
+    pub fn get_realm(&self) -> &str {
+        &self.realm
+    }
+
+    /// Gets the status code an `error` will be sent with
+    pub fn status_for(&self, error: &AuthError) -> StatusCode {
+        (self.status)(error)
+    }
+
+    /// Gets the character set basic authentication credentials are decoded with
+    #[cfg(feature = "auth-basic")]
+    pub fn get_basic_charset(&self) -> BasicCharset {
+        self.basic_charset
+    }
+
+    /// Gets the maximum length in bytes of an `Authorization` or `Proxy-Authorization` header
+    pub fn get_max_header_len(&self) -> usize {
+        self.max_header_len
+    }
+
+    /// Gets the maximum length in bytes of decoded basic authentication credentials
+    pub fn get_max_basic_len(&self) -> usize {
+        self.max_basic_len
+    }
+
+    /// Gets the maximum length in bytes of basic authentication identifiers and digest usernames
+    pub fn get_max_username_len(&self) -> usize {
+        self.max_username_len
+    }
+
+    /// Gets the maximum length in bytes of basic authentication passwords
+    pub fn get_max_password_len(&self) -> usize {
+        self.max_password_len
+    }
+
+    /// Gets the maximum length in bytes of bearer tokens and API keys
+    pub fn get_max_token_len(&self) -> usize {
+        self.max_token_len
+    }
+
+    /// Gets whether schemes are matched regardless of case
+    pub fn get_case_insensitive_schemes(&self) -> bool {
+        self.case_insensitive_schemes
+    }
+
+    /// Gets whether bearer tokens are checked against the `b64token` syntax from RFC 6750
+    #[cfg(feature = "auth-bearer")]
+    pub fn get_strict_bearer(&self) -> bool {
+        self.strict_bearer
+    }
+
+    /// Gets whether bearer tokens are also read from an `access_token` query parameter
+    #[cfg(feature = "auth-bearer")]
+    pub fn get_bearer_query(&self) -> bool {
+        self.bearer_query
+    }
+
+    /// Gets the configuration set for a request, falling back to the default
+    pub(crate) fn from_parts(parts: &Parts) -> Self {
+        parts.extensions.get::<Self>().cloned().unwrap_or_default()
+    }
+
+    /// Creates a rejection for `error` with the configured status and a basic authentication challenge
+    #[cfg(feature = "auth-basic")]
+    pub(crate) fn reject_basic(&self, error: AuthError) -> AuthRejection {
+        self.reject(error, [self.basic_challenge()])
+    }
+
+    /// Creates a rejection for `error` with the configured status and a bearer challenge as described in RFC 6750
+    #[cfg(feature = "auth-bearer")]
+    pub(crate) fn reject_bearer(&self, error: AuthError) -> AuthRejection {
+        let challenge = self.bearer_challenge(Some(&error));
+        self.reject(error, [challenge])
+    }
+
+    /// Creates a rejection for a bearer token lacking some of `scopes`, with a bearer challenge listing all of them as described in RFC 6750
+    #[cfg(feature = "auth-bearer")]
+    pub(crate) fn reject_scope(&self, scopes: &[&str]) -> AuthRejection {
+        let error = AuthError::InsufficientScope;
+        let challenge = self
+            .bearer_challenge(Some(&error))
+            .param("scope", scopes.join(" "));
+        self.reject(error, [challenge])
+    }
+
+    /// Creates a rejection for `error` with the configured status and both basic and bearer challenges
+    ///
+    /// The bearer challenge only gets an error code if `error` came from a bearer token, as basic authentication failures have nothing to do with it.
+    #[cfg(all(feature = "auth-basic", feature = "auth-bearer"))]
+    pub(crate) fn reject_any(&self, error: AuthError, bearer: bool) -> AuthRejection {
+        let challenges = [
+            self.basic_challenge(),
+            self.bearer_challenge(bearer.then_some(&error)),
+        ];
+        self.reject(error, challenges)
+    }
+
+    /// Creates a basic authentication challenge for the configured realm, asking for UTF-8 as RFC 7617 recommends
+    #[cfg(feature = "auth-basic")]
+    fn basic_challenge(&self) -> Challenge {
+        Challenge::new("Basic")
+            .param("realm", self.realm.clone())
+            .param("charset", "UTF-8")
+    }
+
+    /// Creates a bearer challenge for the configured realm, with an error code if `error` has one
+    #[cfg(feature = "auth-bearer")]
+    fn bearer_challenge(&self, error: Option<&AuthError>) -> Challenge {
+        let challenge = Challenge::new("Bearer").param("realm", self.realm.clone());
+        match error.and_then(AuthError::bearer_code) {
+            Some(code) => challenge.param("error", code),
+            None => challenge,
+        }
+    }
+
+    /// Creates a rejection for `error` with the configured status and challenges for wherever a session can be sent, i.e. a `cookie` with the given name and/or a `bearer` token
+    ///
+    /// Cookies don't have a registered scheme, but `401 Unauthorized` responses still need a challenge, so this uses the `Cookie` scheme from the cookie authentication draft which names the cookie to send.
+    #[cfg(feature = "auth-session")]
+    pub(crate) fn reject_session(
+        &self,
+        error: AuthError,
+        cookie: Option<&str>,
+        bearer: bool,
+    ) -> AuthRejection {
+        let cookie = cookie.map(|name| {
+            Challenge::new("Cookie")
+                .param("realm", self.realm.clone())
+                .param("cookie-name", name)
+        });
+        let bearer = bearer.then(|| Challenge::new("Bearer").param("realm", self.realm.clone()));
+        self.reject(error, cookie.into_iter().chain(bearer))
+    }
+
+    /// Creates a rejection for `error` with the configured status and an API key challenge
+    ///
+    /// API keys don't have a registered scheme, but `401 Unauthorized` responses still need a challenge, so this uses the common `ApiKey` scheme.
+    #[cfg(feature = "auth-api-key")]
+    pub(crate) fn reject_api_key(&self, error: AuthError) -> AuthRejection {
+        self.reject(
+            error,
+            [Challenge::new("ApiKey").param("realm", self.realm.clone())],
+        )
+    }
+
+    /// Creates a rejection for `error` with the configured status and `challenges`
+    #[cfg(any(
+        feature = "auth-basic",
+        feature = "auth-bearer",
+        feature = "auth-digest",
+        feature = "auth-session"
+    ))]
+    pub(crate) fn reject(
+        &self,
+        error: AuthError,
+        challenges: impl IntoIterator<Item = Challenge>,
+    ) -> AuthRejection {
+        let status = self.status_for(&error);
+        challenges.into_iter().fold(
+            AuthRejection::new(error).with_status(status),
+            |rejection, challenge| rejection.with_challenge(challenge),
+        )
+    }
+}
+
+impl Default for AuthConfig {
+    /// Creates a configuration with a realm of `"Restricted"` which sends errors with their default [status](AuthError::status), decodes basic authentication as UTF-8, matches schemes regardless of case, only reads bearer tokens from headers, doesn't check their syntax and uses the default length limits given by each setter
+    fn default() -> Self {
+        Self {
+            realm: Cow::Borrowed("Restricted"),
+            status: AuthError::status,
+            #[cfg(feature = "auth-basic")]
+            basic_charset: BasicCharset::Utf8,
+            max_header_len: 8 * 1024,
+            max_basic_len: 4 * 1024,
+            max_username_len: 1024,
+            max_password_len: 1024,
+            max_token_len: 8 * 1024,
+            case_insensitive_schemes: true,
+            #[cfg(feature = "auth-bearer")]
+            strict_bearer: false,
+            #[cfg(feature = "auth-bearer")]
+            bearer_query: false,
+        }
+    }
+}
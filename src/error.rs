@@ -0,0 +1,164 @@
+use axum_core::response::{IntoResponse, Response};
+use http::StatusCode;
+use std::{borrow::Cow, fmt};
+
+/// Error given off when an authentication extractor rejects a request
+///
+/// Every extractor in this crate wraps this in its [AuthRejection](crate::AuthRejection) so you can match on exactly what went wrong instead of on the message text:
+///
+/// ```no_run
+/// use axum_auth::{AuthBearer, AuthError, AuthRejection};
+///
+/// /// Handler which explains to the client why their token was refused
+/// async fn handler(auth: Result<AuthBearer, AuthRejection>) -> String {
+///     match auth.map_err(AuthRejection::into_error) {
+///         Ok(AuthBearer(token)) => format!("Found a bearer token: {}", token.expose_secret()),
+///         Err(AuthError::MissingHeader) => "Please log in first".to_string(),
+///         Err(err) => format!("Couldn't authenticate: {}", err),
+///     }
+/// }
+/// ```
+///
+/// New variants may be added in the future, so matching on this requires a wildcard arm.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum AuthError {
+    /// The `Authorization` header is completely missing
+    MissingHeader,
+    /// The `Authorization` header contains invalid characters (i.e. non-ASCII)
+    InvalidCharacters,
+    /// The `Authorization` header is for another scheme than the expected one
+    WrongScheme {
+        /// Name of the authentication scheme which was expected, e.g. `Basic`, or `Basic or Bearer` when several were accepted
+        expected: &'static str,
+    },
+    /// Basic authentication wasn't properly base64-encoded
+    InvalidBase64,
+    /// Decoded credentials weren't valid UTF-8
+    InvalidUtf8,
+    /// A bearer token was present but empty
+    EmptyToken,
+    /// A bearer token didn't follow the `b64token` syntax from RFC 6750, which is only checked in [strict mode](crate::AuthConfig::strict_bearer)
+    MalformedToken,
+    /// A bearer token was sent in more than one place, e.g. both the `Authorization` header and the query, or more than once in the same place
+    DuplicateToken,
+    /// The `Authorization` header or the credentials inside of it were longer than the limits set in the [AuthConfig](crate::AuthConfig)
+    TooLarge,
+    /// An API key is completely missing from where it's expected to be
+    MissingApiKey,
+    /// An API key was present but empty, contained invalid characters or was given more than once
+    MalformedApiKey,
+    /// A session cookie is completely missing
+    MissingSession,
+    /// A session cookie was present but its signature didn't match, it couldn't be decrypted or its contents were malformed
+    InvalidSession,
+    /// A session cookie was valid but has expired
+    ExpiredSession,
+    /// The `Authorization` header's parameters were malformed, missing or used unsupported options
+    InvalidParameters,
+    /// Credentials were well-formed but didn't match any known user
+    InvalidCredentials,
+    /// A digest used a nonce which has expired or a nonce count which has already been seen
+    StaleNonce,
+    /// A bearer token was well-formed but is expired, revoked or otherwise invalid
+    InvalidToken,
+    /// A bearer token was valid but lacks scopes required for the route, see [RequireScopes](crate::RequireScopes)
+    InsufficientScope,
+    /// Credentials were valid but a [Policy](crate::Policy) didn't let them through, see [Authorized](crate::Authorized)
+    Forbidden {
+        /// Reason the policy gave for denying the request, which is sent to the client
+        reason: Cow<'static, str>,
+    },
+    /// Credentials couldn't be checked because something they're checked against is unavailable, e.g. a key set which couldn't be fetched
+    Unavailable,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHeader => write!(f, "`Authorization` header is missing"),
+            Self::InvalidCharacters => {
+                write!(f, "`Authorization` header contains invalid characters")
+            }
+            Self::WrongScheme { expected: "Bearer" } => {
+                write!(f, "`Authorization` header must be a bearer token")
+            }
+            Self::WrongScheme { expected } => write!(
+                f,
+                "`Authorization` header must be for {} authentication",
+                expected.to_lowercase()
+            ),
+            Self::InvalidBase64 | Self::InvalidUtf8 => write!(
+                f,
+                "`Authorization` header's basic authentication was improperly encoded"
+            ),
+            Self::EmptyToken => write!(f, "`Authorization` header's bearer token is empty"),
+            Self::MalformedToken => write!(f, "`Authorization` header's bearer token is malformed"),
+            Self::DuplicateToken => write!(f, "Bearer token was sent more than once"),
+            Self::TooLarge => write!(f, "Credentials are too large"),
+            Self::MissingApiKey => write!(f, "API key is missing"),
+            Self::MalformedApiKey => write!(f, "API key is malformed"),
+            Self::MissingSession => write!(f, "Session cookie is missing"),
+            Self::InvalidSession => write!(f, "Session cookie is invalid"),
+            Self::ExpiredSession => write!(f, "Session has expired"),
+            Self::InvalidParameters => write!(f, "`Authorization` header's parameters are invalid"),
+            Self::InvalidCredentials => write!(f, "Invalid credentials"),
+            Self::StaleNonce => write!(f, "`Authorization` header's nonce is stale"),
+            Self::InvalidToken => write!(f, "Invalid bearer token"),
+            Self::InsufficientScope => write!(f, "Bearer token lacks the required scopes"),
+            Self::Forbidden { reason } => write!(f, "Forbidden: {}", reason),
+            Self::Unavailable => write!(f, "Authentication is temporarily unavailable"),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+impl AuthError {
+    /// Creates an [AuthError::Forbidden] for a [Policy](crate::Policy) to deny requests with, e.g. `requires the admin role`
+    pub fn forbidden(reason: impl Into<Cow<'static, str>>) -> Self {
+        Self::Forbidden {
+            reason: reason.into(),
+        }
+    }
+
+    /// Status code which this error is sent to clients with by default
+    ///
+    /// This is `401 Unauthorized` for everything except [AuthError::InsufficientScope] and [AuthError::Forbidden], which are `403 Forbidden` as authenticating again won't help, and [AuthError::Unavailable], which is `503 Service Unavailable` as it isn't the client's fault.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::InsufficientScope | Self::Forbidden { .. } => StatusCode::FORBIDDEN,
+            Self::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    /// Checks if this error means no credentials were sent at all, as opposed to credentials which were sent but aren't any good
+    ///
+    /// This is what [OptionalAuth](crate::OptionalAuth) uses to decide between letting requests through anonymously and rejecting them.
+    pub fn is_missing(&self) -> bool {
+        matches!(
+            self,
+            Self::MissingHeader | Self::MissingApiKey | Self::MissingSession
+        )
+    }
+
+    /// Error code for this error in a bearer challenge as described in RFC 6750
+    ///
+    /// Requests without any bearer credentials shouldn't be given an error code, so this gives [None] for them.
+    #[cfg(feature = "auth-bearer")]
+    pub(crate) fn bearer_code(&self) -> Option<&'static str> {
+        match self {
+            Self::MissingHeader | Self::WrongScheme { .. } | Self::Unavailable => None,
+            Self::InvalidToken => Some("invalid_token"),
+            Self::InsufficientScope => Some("insufficient_scope"),
+            _ => Some("invalid_request"),
+        }
+    }
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        (self.status(), self.to_string()).into_response()
+    }
+}
@@ -0,0 +1,159 @@
+#[cfg(any(
+    feature = "auth-basic",
+    feature = "auth-bearer",
+    feature = "auth-digest",
+    feature = "auth-session"
+))]
+use crate::{AuthConfig, AuthError};
+#[cfg(any(
+    feature = "auth-api-key",
+    feature = "auth-basic",
+    feature = "auth-bearer",
+    feature = "auth-digest",
+    feature = "auth-session"
+))]
+use http::HeaderMap;
+
+/// Gets the `Authorization` header from request headers and splits it into its scheme and contents
+#[cfg(any(
+    feature = "auth-basic",
+    feature = "auth-bearer",
+    feature = "auth-digest",
+    feature = "auth-session"
+))]
+pub(crate) fn auth_header<'a>(
+    headers: &'a HeaderMap,
+    config: &AuthConfig,
+) -> Result<(&'a str, &'a str), AuthError> {
+    credentials_header(headers, http::header::AUTHORIZATION, config)
+}
+
+/// Gets the `Proxy-Authorization` header from request headers and splits it into its scheme and contents
+#[cfg(any(feature = "auth-basic", feature = "auth-bearer"))]
+pub(crate) fn proxy_auth_header<'a>(
+    headers: &'a HeaderMap,
+    config: &AuthConfig,
+) -> Result<(&'a str, &'a str), AuthError> {
+    credentials_header(headers, http::header::PROXY_AUTHORIZATION, config)
+}
+
+/// Gets a header containing credentials and splits it into its scheme and contents, rejecting it if it's over the configured length
+#[cfg(any(
+    feature = "auth-basic",
+    feature = "auth-bearer",
+    feature = "auth-digest",
+    feature = "auth-session"
+))]
+fn credentials_header<'a>(
+    headers: &'a HeaderMap,
+    name: http::header::HeaderName,
+    config: &AuthConfig,
+) -> Result<(&'a str, &'a str), AuthError> {
+    // Get authorisation header, refusing to look at oversized ones
+    let authorisation = headers.get(name).ok_or(AuthError::MissingHeader)?;
+    if authorisation.len() > config.get_max_header_len() {
+        return Err(AuthError::TooLarge);
+    }
+    let authorisation = authorisation
+        .to_str()
+        .map_err(|_| AuthError::InvalidCharacters)?;
+
+    // Split scheme from contents, a lone scheme has empty contents
+    let (scheme, contents) = authorisation.split_once(' ').unwrap_or((authorisation, ""));
+
+    // Schemes are case-insensitive, so give back our spelling of them for matching on
+    let scheme = match config.get_case_insensitive_schemes() {
+        true => SCHEMES
+            .into_iter()
+            .find(|known| known.eq_ignore_ascii_case(scheme))
+            .unwrap_or(scheme),
+        false => scheme,
+    };
+    Ok((scheme, contents))
+}
+
+/// Schemes which the extractors in this crate match on
+#[cfg(any(
+    feature = "auth-basic",
+    feature = "auth-bearer",
+    feature = "auth-digest",
+    feature = "auth-session"
+))]
+const SCHEMES: [&str; 3] = ["Basic", "Bearer", "Digest"];
+
+/// Parses a comma-separated list of auth-params such as `realm="example", qop=auth` into lowercase names and unquoted values
+///
+/// Gives [None] if the list is malformed or contains the same parameter twice.
+#[cfg(feature = "auth-digest")]
+pub(crate) fn auth_params(input: &str) -> Option<std::collections::HashMap<String, String>> {
+    let mut params = std::collections::HashMap::new();
+    let mut rest = input.trim_start_matches([' ', '\t', ',']);
+    while !rest.is_empty() {
+        // Get the name up to the equals sign
+        let (name, after) = rest.split_once('=')?;
+        let name = name.trim_end_matches([' ', '\t']).to_ascii_lowercase();
+        if name.is_empty() || !name.bytes().all(is_tchar) {
+            return None;
+        }
+        let after = after.trim_start_matches([' ', '\t']);
+
+        // Get the value as either a quoted string or a bare token
+        let (value, after) = if let Some(quoted) = after.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let end = loop {
+                match chars.next()? {
+                    (_, '\\') => value.push(chars.next()?.1),
+                    (ind, '"') => break ind + 1,
+                    (_, c) => value.push(c),
+                }
+            };
+            (value, &quoted[end..])
+        } else {
+            let end = after.find([',', ' ', '\t']).unwrap_or(after.len());
+            let value = &after[..end];
+            if value.is_empty() || !value.bytes().all(is_tchar) {
+                return None;
+            }
+            (value.to_string(), &after[end..])
+        };
+
+        // Move onto the next parameter, which must be after a comma
+        if params.insert(name, value).is_some() {
+            return None;
+        }
+        let after = after.trim_start_matches([' ', '\t']);
+        rest = match after.strip_prefix(',') {
+            Some(after) => after.trim_start_matches([' ', '\t', ',']),
+            None if after.is_empty() => after,
+            None => return None,
+        };
+    }
+    Some(params)
+}
+
+/// Checks if a byte is allowed in a token as described in RFC 9110
+#[cfg(any(feature = "auth-digest", feature = "auth-session"))]
+pub(crate) fn is_tchar(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte)
+}
+
+/// Gets the value of the first cookie called `name` from request headers, without any quotes around it
+#[cfg(any(feature = "auth-api-key", feature = "auth-session"))]
+pub(crate) fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(http::header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .find_map(|cookie| {
+            let (key, value) = cookie.trim().split_once('=')?;
+            (key.trim_end() == name).then(|| value.trim_start())
+        })
+        .map(|value| {
+            value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value)
+        })
+}
@@ -0,0 +1,333 @@
+use crate::{AuthError, BasicVerifier};
+use async_trait::async_trait;
+use md5::{Digest, Md5};
+use sha1::Sha1;
+use std::{
+    collections::HashMap,
+    fmt, io,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant, SystemTime},
+};
+use subtle::ConstantTimeEq;
+
+/// Verifier for basic authentication which checks credentials against an Apache-style htpasswd file
+///
+/// This is enabled via the `auth-htpasswd` feature.
+///
+/// Each line of the file should be a `user:hash` pair, with blank lines and lines starting with `#` being skipped. The following hash formats are supported:
+///
+/// - bcrypt, e.g. `$2y$05$...` as made by `htpasswd -B`
+/// - Apache's MD5, e.g. `$apr1$...` as made by `htpasswd -m`
+/// - MD5 crypt, e.g. `$1$...` as made by `openssl passwd -1`
+/// - SHA-1, e.g. `{SHA}...` as made by `htpasswd -s`
+/// - SHA-256 and SHA-512 crypt, e.g. `$5$...` and `$6$...` as made by `htpasswd -2` and `htpasswd -5`
+/// - DES crypt, i.e. any 13 characters long hash as made by `htpasswd -d`
+/// - Plaintext, i.e. anything which doesn't look like a hash as made by `htpasswd -p`
+///
+/// Plaintext passwords which happen to be 13 characters long and made up only of letters, digits, `.` and `/` are read as DES crypt hashes, so they never match themselves. Hash any such password instead of storing it as plaintext.
+///
+/// Hashes in any other format starting with `$id$` or `{SCHEME}`, such as `{SSHA}` or Argon2, are unsupported and never match, rather than being mistaken for plaintext passwords.
+///
+/// All comparisons are done in constant time, and hashes are checked on tokio's blocking thread pool as bcrypt is slow by design. Once verified, this gives the user's name.
+///
+/// Files are checked for changes at most once every [check_interval](HtpasswdVerifier::check_interval), and reloaded if they've been modified since, so users can be added without restarting.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{routing::get, Router};
+/// use axum_auth::{HtpasswdVerifier, VerifiedBasic};
+///
+/// /// Handler which only runs for users in the htpasswd file
+/// async fn handler(VerifiedBasic(user): VerifiedBasic<HtpasswdVerifier>) -> String {
+///     format!("Hello, {}", user)
+/// }
+///
+/// let verifier = HtpasswdVerifier::from_file("/etc/myapp/.htpasswd").unwrap();
+/// let app: Router = Router::new().route("/", get(handler)).with_state(verifier);
+/// ```
+#[derive(Clone)]
+pub struct HtpasswdVerifier {
+    path: Option<Arc<PathBuf>>,
+    check_interval: Duration,
+    cache: Arc<RwLock<HtpasswdCache>>,
+}
+
+/// Currently loaded users, along with when their file was modified and when it was last checked
+struct HtpasswdCache {
+    users: HtpasswdUsers,
+    modified: Option<SystemTime>,
+    checked: Instant,
+}
+
+impl HtpasswdVerifier {
+    /// Creates a verifier from the contents of an htpasswd file which are never reloaded
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum_auth::{BasicVerifier, HtpasswdVerifier};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let verifier = HtpasswdVerifier::from_contents(
+    ///     "# Made using `htpasswd -m`\n\
+    ///      admin:$apr1$r31....$wavFJkmsAg3OxDgOjqIAo/\n",
+    /// );
+    ///
+    /// assert_eq!(verifier.verify("admin", Some("hunter2")).await.unwrap(), "admin");
+    /// assert!(verifier.verify("admin", Some("hunter3")).await.is_err());
+    /// assert!(verifier.verify("root", Some("hunter2")).await.is_err());
+    /// # }
+    /// ```
+    pub fn from_contents(contents: &str) -> Self {
+        Self::new(None, parse_htpasswd(contents), None)
+    }
+
+    /// Creates a verifier from an htpasswd file on disk, which is loaded straight away and reloaded once changed
+    pub fn from_file(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let users = parse_htpasswd(&std::fs::read_to_string(&path)?);
+        let modified = std::fs::metadata(&path)?.modified().ok();
+        Ok(Self::new(Some(path), users, modified))
+    }
+
+    /// Sets the minimum time between checks for changes to the file, defaulting to 5 seconds
+    pub fn check_interval(mut self, check_interval: Duration) -> Self {
+        self.check_interval = check_interval;
+        self
+    }
+
+    /// Reloads users from this verifier's file straight away
+    ///
+    /// Verifiers created using [HtpasswdVerifier::from_contents] have nowhere to reload from, so this does nothing for them.
+    pub async fn reload(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let modified = tokio::fs::metadata(path.as_ref()).await?.modified().ok();
+        let users = parse_htpasswd(&tokio::fs::read_to_string(path.as_ref()).await?);
+        let mut cache = self.cache.write().unwrap();
+        cache.users = users;
+        cache.modified = modified;
+        Ok(())
+    }
+
+    fn new(path: Option<PathBuf>, users: HtpasswdUsers, modified: Option<SystemTime>) -> Self {
+        Self {
+            path: path.map(Arc::new),
+            check_interval: Duration::from_secs(5),
+            cache: Arc::new(RwLock::new(HtpasswdCache {
+                users,
+                modified,
+                checked: Instant::now(),
+            })),
+        }
+    }
+
+    /// Reloads users if the check interval has passed and the file has been modified since, carrying on with the old ones if that fails
+    async fn reload_if_changed(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        {
+            let mut cache = self.cache.write().unwrap();
+            if cache.checked.elapsed() < self.check_interval {
+                return;
+            }
+            cache.checked = Instant::now();
+        }
+        let modified = match tokio::fs::metadata(path.as_ref()).await {
+            Ok(metadata) => metadata.modified().ok(),
+            Err(_) => return,
+        };
+        if modified.is_none() || modified != self.cache.read().unwrap().modified {
+            let _ = self.reload().await;
+        }
+    }
+}
+
+impl fmt::Debug for HtpasswdVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HtpasswdVerifier")
+            .field("path", &self.path)
+            .field("users", &self.cache.read().unwrap().users.hashes.len())
+            .field("check_interval", &self.check_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl BasicVerifier for HtpasswdVerifier {
+    type User = String;
+
+    async fn verify(&self, id: &str, password: Option<&str>) -> Result<Self::User, AuthError> {
+        self.reload_if_changed().await;
+        let (hash, dummy) = {
+            let users = &self.cache.read().unwrap().users;
+            (users.hashes.get(id).cloned(), users.dummy.clone())
+        };
+        let Some(password) = password else {
+            return Err(AuthError::InvalidCredentials);
+        };
+
+        // Check unknown users against someone else's hash so they take as long as wrong passwords
+        let (known, hash) = match (hash, dummy) {
+            (Some(hash), _) => (true, hash),
+            (None, Some(dummy)) => (false, dummy),
+            (None, None) => return Err(AuthError::InvalidCredentials),
+        };
+
+        // Check the hash off of the async runtime as it may be slow on purpose
+        let password = password.to_string();
+        let matches = tokio::task::spawn_blocking(move || check_hash(&hash, &password))
+            .await
+            .map_err(|_| AuthError::Unavailable)?;
+        match known && matches {
+            true => Ok(id.to_string()),
+            false => Err(AuthError::InvalidCredentials),
+        }
+    }
+}
+
+/// Users loaded from an htpasswd file
+struct HtpasswdUsers {
+    /// Hash of each user's password
+    hashes: HashMap<String, String>,
+    /// Hash of the first user's password, which unknown users are checked against to hide that they don't exist
+    dummy: Option<String>,
+}
+
+/// Parses the contents of an htpasswd file into users and their hashes, keeping the first entry for duplicate users
+fn parse_htpasswd(contents: &str) -> HtpasswdUsers {
+    let mut hashes = HashMap::new();
+    let mut dummy = None;
+    for line in contents.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some((user, hash)) = line.split_once(':') {
+            dummy.get_or_insert_with(|| hash.to_string());
+            hashes
+                .entry(user.to_string())
+                .or_insert_with(|| hash.to_string());
+        }
+    }
+    HtpasswdUsers { hashes, dummy }
+}
+
+/// Checks a password against a hash in any of the supported formats
+fn check_hash(hash: &str, password: &str) -> bool {
+    if let Some(digest) = hash.strip_prefix("{SHA}") {
+        let computed = base64::encode(Sha1::digest(password.as_bytes()));
+        computed.as_bytes().ct_eq(digest.as_bytes()).into()
+    } else if let Some(rest) = hash.strip_prefix("$apr1$") {
+        let salt = rest.split('$').next().unwrap_or_default();
+        let computed = apr1(password.as_bytes(), salt.as_bytes());
+        computed.as_bytes().ct_eq(hash.as_bytes()).into()
+    } else if ["$1$", "$2a$", "$2b$", "$2y$", "$5$", "$6$"]
+        .iter()
+        .any(|prefix| hash.starts_with(prefix))
+        || is_des_crypt(hash)
+    {
+        pwhash::unix::verify(password, hash)
+    } else if is_unsupported_hash(hash) {
+        false
+    } else {
+        password.as_bytes().ct_eq(hash.as_bytes()).into()
+    }
+}
+
+/// Checks if a hash looks like one in a format which isn't supported, i.e. starting with `$id$` or `{SCHEME}`
+///
+/// These must never be compared as plaintext, otherwise the hash itself would work as the password.
+fn is_unsupported_hash(hash: &str) -> bool {
+    let prefixed = |open, close| {
+        hash.strip_prefix(open)
+            .and_then(|rest| rest.split_once(close))
+            .is_some_and(|(id, _)| !id.is_empty())
+    };
+    prefixed('$', '$') || prefixed('{', '}')
+}
+
+/// Checks if a hash looks like it was made using DES crypt, which is 13 characters from its base64 alphabet
+fn is_des_crypt(hash: &str) -> bool {
+    hash.len() == 13
+        && hash
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'.' || b == b'/')
+}
+
+/// Alphabet used by crypt-style base64
+const ITOA64: &[u8; 64] = b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Hashes a password using Apache's variant of MD5 crypt, giving the full `$apr1$salt$hash` string
+fn apr1(password: &[u8], salt: &[u8]) -> String {
+    const MAGIC: &[u8] = b"$apr1$";
+    let salt = &salt[..salt.len().min(8)];
+
+    // Mix the password, magic and salt with an alternate sum
+    let alternate = Md5::new()
+        .chain_update(password)
+        .chain_update(salt)
+        .chain_update(password)
+        .finalize();
+    let mut ctx = Md5::new()
+        .chain_update(password)
+        .chain_update(MAGIC)
+        .chain_update(salt);
+    for chunk in password.chunks(16) {
+        ctx.update(&alternate[..chunk.len()]);
+    }
+    let mut len = password.len();
+    while len > 0 {
+        match len & 1 {
+            1 => ctx.update([0]),
+            _ => ctx.update(&password[..1]),
+        }
+        len >>= 1;
+    }
+    let mut sum = ctx.finalize();
+
+    // Slow things down with a thousand more rounds
+    for round in 0..1000 {
+        let mut ctx = Md5::new();
+        match round % 2 {
+            1 => ctx.update(password),
+            _ => ctx.update(sum),
+        }
+        if round % 3 != 0 {
+            ctx.update(salt);
+        }
+        if round % 7 != 0 {
+            ctx.update(password);
+        }
+        match round % 2 {
+            1 => ctx.update(sum),
+            _ => ctx.update(password),
+        }
+        sum = ctx.finalize();
+    }
+
+    // Encode the sum in its own shuffled order
+    let mut out = String::from_utf8_lossy(MAGIC).into_owned();
+    out.push_str(&String::from_utf8_lossy(salt));
+    out.push('$');
+    let groups = [(0, 6, 12), (1, 7, 13), (2, 8, 14), (3, 9, 15), (4, 10, 5)];
+    for (a, b, c) in groups {
+        let value = (sum[a] as u32) << 16 | (sum[b] as u32) << 8 | sum[c] as u32;
+        push_itoa64(&mut out, value, 4);
+    }
+    push_itoa64(&mut out, sum[11] as u32, 2);
+    out
+}
+
+/// Pushes the lowest `count` groups of six bits from `value` in crypt-style base64
+fn push_itoa64(out: &mut String, mut value: u32, count: usize) {
+    for _ in 0..count {
+        out.push(ITOA64[(value & 0x3f) as usize] as char);
+        value >>= 6;
+    }
+}
@@ -0,0 +1,289 @@
+use crate::{AuthError, BearerValidator, HasClaims, HasScopes, Secret};
+use async_trait::async_trait;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Number of cached results after which expired ones start getting swept out on every insert
+const SWEEP_THRESHOLD: usize = 1024;
+
+/// Most results which are ever cached at once
+const MAX_CACHED: usize = 8192;
+
+/// Client for OAuth 2.0 token introspection as described in RFC 7662, used to validate opaque bearer tokens
+///
+/// This is enabled via the `auth-introspection` feature.
+///
+/// Tokens are posted to the introspection endpoint using the client credentials given, which are sent using basic authentication. Active tokens are cached until they expire (capped by [max_ttl](Introspector::max_ttl)) and inactive tokens are cached for [negative_ttl](Introspector::negative_ttl), so the endpoint isn't hit on every request. Results are cached under a hash of their token and the cache is capped in size; once it fills up, inactive tokens stop being cached and active ones push out whichever result expires soonest.
+///
+/// This implements [BearerValidator], so it can be used with the [ValidatedBearer](crate::ValidatedBearer) extractor to give handlers an [IntrospectedToken].
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{routing::get, Router};
+/// use axum_auth::{Introspector, ValidatedBearer};
+///
+/// /// Handler which greets the token's subject
+/// async fn handler(ValidatedBearer(token): ValidatedBearer<Introspector>) -> String {
+///     format!("Hello, {}", token.sub.unwrap_or_default())
+/// }
+///
+/// let introspector = Introspector::new(
+///     "https://auth.example.com/oauth2/introspect",
+///     "my-api",
+///     "s3cr3t",
+/// );
+///
+/// let app: Router = Router::new().route("/", get(handler)).with_state(introspector);
+/// ```
+#[derive(Clone)]
+pub struct Introspector {
+    client: reqwest::Client,
+    endpoint: Arc<str>,
+    client_id: Arc<str>,
+    client_secret: Arc<Secret>,
+    max_ttl: Duration,
+    negative_ttl: Duration,
+    cache: Arc<Mutex<HashMap<[u8; 32], CachedResult>>>,
+}
+
+/// Introspection result which is cached until it expires
+struct CachedResult {
+    token: Option<IntrospectedToken>,
+    expires: Instant,
+}
+
+impl Introspector {
+    /// Creates a new introspector which posts tokens to `endpoint`, authenticating using the client credentials given
+    ///
+    /// Requests made using the default client time out after 10 seconds, or 5 seconds if a connection can't be made; use [Introspector::client] to change this.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the TLS backend can't be initialized, like [reqwest::Client::new].
+    pub fn new(
+        endpoint: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(5))
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("couldn't create introspection client"),
+            endpoint: endpoint.into().into(),
+            client_id: client_id.into().into(),
+            client_secret: Arc::new(Secret::new(client_secret.into())),
+            max_ttl: Duration::from_secs(5 * 60),
+            negative_ttl: Duration::from_secs(60),
+            cache: Arc::default(),
+        }
+    }
+
+    /// Sets the longest time active tokens are cached for, defaulting to 5 minutes
+    ///
+    /// Tokens are never cached past their `exp`, but revocations also won't be noticed until this passes.
+    pub fn max_ttl(mut self, max_ttl: Duration) -> Self {
+        self.max_ttl = max_ttl;
+        self
+    }
+
+    /// Sets how long inactive tokens are cached for, defaulting to 60 seconds
+    pub fn negative_ttl(mut self, negative_ttl: Duration) -> Self {
+        self.negative_ttl = negative_ttl;
+        self
+    }
+
+    /// Sets the HTTP client used to reach the introspection endpoint, e.g. to use custom timeouts, proxies or root certificates
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Introspects a `token`, returning it if it's active
+    ///
+    /// Inactive tokens give [AuthError::InvalidToken], whilst endpoints which can't be reached or give back garbage give [AuthError::Unavailable].
+    pub async fn introspect(&self, token: &str) -> Result<IntrospectedToken, AuthError> {
+        // Use the cached result if there's one
+        let key: [u8; 32] = Sha256::digest(token).into();
+        if let Some(cached) = self.cached(&key) {
+            return cached.ok_or(AuthError::InvalidToken);
+        }
+
+        // Ask the endpoint then cache the result until it expires
+        let introspected = self.request(token).await?;
+        let now = Instant::now();
+        let (token_result, expires) = match introspected {
+            Some(introspected) => {
+                let ttl = match introspected.exp {
+                    Some(exp) => Duration::from_secs(exp.saturating_sub(unix_now())),
+                    None => self.max_ttl,
+                };
+                (Some(introspected), now + ttl.min(self.max_ttl))
+            }
+            None => (None, now + self.negative_ttl),
+        };
+        self.cache_result(key, token_result.clone(), expires, now);
+        token_result.ok_or(AuthError::InvalidToken)
+    }
+
+    /// Gets the cached result for a token's hash if it hasn't expired
+    fn cached(&self, key: &[u8; 32]) -> Option<Option<IntrospectedToken>> {
+        let cache = self.cache.lock().unwrap();
+        let cached = cache.get(key)?;
+        (cached.expires > Instant::now()).then(|| cached.token.clone())
+    }
+
+    /// Caches a result under its token's hash, keeping the cache within [MAX_CACHED]
+    fn cache_result(
+        &self,
+        key: [u8; 32],
+        token: Option<IntrospectedToken>,
+        expires: Instant,
+        now: Instant,
+    ) {
+        let mut cache = self.cache.lock().unwrap();
+        if cache.len() >= SWEEP_THRESHOLD {
+            cache.retain(|_, cached| cached.expires > now);
+        }
+
+        // Once full, only make room for active tokens so floods of junk can't push them out
+        if cache.len() >= MAX_CACHED && !cache.contains_key(&key) {
+            if token.is_none() {
+                return;
+            }
+            let soonest = cache
+                .iter()
+                .min_by_key(|(_, cached)| cached.expires)
+                .map(|(key, _)| *key);
+            if let Some(soonest) = soonest {
+                cache.remove(&soonest);
+            }
+        }
+        cache.insert(key, CachedResult { token, expires });
+    }
+
+    /// Posts a token to the introspection endpoint, returning it if it's active
+    async fn request(&self, token: &str) -> Result<Option<IntrospectedToken>, AuthError> {
+        let res = self
+            .client
+            .post(&*self.endpoint)
+            .basic_auth(&self.client_id, Some(self.client_secret.expose_secret()))
+            .header(http::header::ACCEPT, "application/json")
+            .form(&[("token", token), ("token_type_hint", "access_token")])
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .map_err(|_| AuthError::Unavailable)?;
+        let body = res.bytes().await.map_err(|_| AuthError::Unavailable)?;
+        let introspected: IntrospectedToken =
+            serde_json::from_slice(&body).map_err(|_| AuthError::Unavailable)?;
+
+        // Double-check time-based claims in case the endpoint didn't
+        let now = unix_now();
+        let expired = introspected.exp.is_some_and(|exp| exp <= now);
+        let early = introspected.nbf.is_some_and(|nbf| nbf > now);
+        Ok((introspected.active && !expired && !early).then_some(introspected))
+    }
+}
+
+impl fmt::Debug for Introspector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Introspector")
+            .field("endpoint", &self.endpoint)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &self.client_secret)
+            .field("max_ttl", &self.max_ttl)
+            .field("negative_ttl", &self.negative_ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl BearerValidator for Introspector {
+    type Principal = IntrospectedToken;
+
+    async fn validate(&self, token: &str) -> Result<Self::Principal, AuthError> {
+        self.introspect(token).await
+    }
+}
+
+/// Active token as described by an introspection endpoint
+///
+/// This is enabled via the `auth-introspection` feature.
+///
+/// Only `active` is required by RFC 7662, so every other standard field is optional. Any non-standard fields the endpoint gives back are kept in [extra](IntrospectedToken::extra).
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct IntrospectedToken {
+    /// Whether the token is active, which is always true once given to handlers
+    pub active: bool,
+    /// Space-separated list of scopes the token has, see [IntrospectedToken::scopes]
+    pub scope: Option<String>,
+    /// Identifier of the client which requested the token
+    pub client_id: Option<String>,
+    /// Human-readable identifier of the resource owner who authorized the token
+    pub username: Option<String>,
+    /// Type of the token, e.g. `Bearer`
+    pub token_type: Option<String>,
+    /// When the token expires as a unix timestamp
+    pub exp: Option<u64>,
+    /// When the token was issued as a unix timestamp
+    pub iat: Option<u64>,
+    /// When the token starts being valid as a unix timestamp
+    pub nbf: Option<u64>,
+    /// Subject of the token, usually the resource owner's identifier
+    pub sub: Option<String>,
+    /// Issuer of the token
+    pub iss: Option<String>,
+    /// Any other fields given back by the endpoint
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl IntrospectedToken {
+    /// Iterates over the scopes this token has
+    pub fn scopes(&self) -> impl Iterator<Item = &str> {
+        self.scope
+            .iter()
+            .flat_map(|scope| scope.split_ascii_whitespace())
+    }
+}
+
+impl HasScopes for IntrospectedToken {
+    fn has_scope(&self, scope: &str) -> bool {
+        self.scopes().any(|own| own == scope)
+    }
+}
+
+impl HasClaims for IntrospectedToken {
+    fn claim(&self, name: &str) -> Option<Cow<'_, str>> {
+        let standard = match name {
+            "scope" => &self.scope,
+            "client_id" => &self.client_id,
+            "username" => &self.username,
+            "token_type" => &self.token_type,
+            "sub" => &self.sub,
+            "iss" => &self.iss,
+            _ => match self.extra.get(name)? {
+                serde_json::Value::String(value) => return Some(Cow::Borrowed(value)),
+                value => return Some(Cow::Owned(value.to_string())),
+            },
+        };
+        standard.as_deref().map(Cow::Borrowed)
+    }
+}
+
+/// Gets the current unix timestamp in seconds
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
@@ -0,0 +1,357 @@
+use crate::AuthError;
+use jsonwebtoken::{
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk, PublicKeyUse},
+    Algorithm, DecodingKey,
+};
+use serde::Deserialize;
+use std::{
+    fmt,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+/// Largest JWKS document which is fetched from a URL, which is far more than any real key set needs
+const MAX_JWKS_LEN: usize = 256 * 1024;
+
+/// Set of JSON Web Keys as described in RFC 7517, used to pick the key a token was signed with
+///
+/// This is enabled via the `auth-jwks` feature.
+///
+/// Keys are picked using the `kid` header of incoming tokens. Key sets loaded from a file or URL are cached for a [ttl](JwkKeySet::ttl) and are fetched again early whenever a token with an unknown `kid` comes in, so signing keys can be rotated without restarting. To stop unknown keys from hammering the source, refetches happen at most once every [cooldown](JwkKeySet::cooldown).
+///
+/// Once created, this can be given to a [JwtDecoder](crate::JwtDecoder) using [JwtDecoder::from_jwks](crate::JwtDecoder::from_jwks) so that the [AuthJwt](crate::AuthJwt) extractor uses it.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{routing::get, Router};
+/// use axum_auth::{AuthJwt, JwkKeySet, JwtDecoder};
+/// use std::time::Duration;
+///
+/// async fn handler(AuthJwt(claims): AuthJwt<serde_json::Value>) -> String {
+///     format!("Claims: {}", claims)
+/// }
+///
+/// let keys = JwkKeySet::from_url("https://auth.example.com/.well-known/jwks.json")
+///     .ttl(Duration::from_secs(15 * 60));
+/// let decoder = JwtDecoder::from_jwks(keys).issuer(&["https://auth.example.com"]);
+///
+/// let app: Router = Router::new().route("/", get(handler)).with_state(decoder);
+/// ```
+#[derive(Clone)]
+pub struct JwkKeySet {
+    source: Arc<JwksSource>,
+    ttl: Duration,
+    cooldown: Duration,
+    cache: Arc<RwLock<JwksCache>>,
+}
+
+/// Where a key set gets (re)loaded from
+enum JwksSource {
+    Static,
+    File(PathBuf),
+    Url(reqwest::Client, String),
+}
+
+/// Currently loaded keys, along with when they were last loaded and when loading was last tried
+#[derive(Default)]
+struct JwksCache {
+    keys: Vec<CachedKey>,
+    loaded: Option<Instant>,
+    attempted: Option<Instant>,
+}
+
+/// Key from a key set which is ready to be used for decoding
+struct CachedKey {
+    kid: Option<String>,
+    algorithms: Vec<Algorithm>,
+    key: DecodingKey,
+}
+
+impl JwkKeySet {
+    /// Creates a key set from a JWKS document which is never reloaded
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use axum_auth::{jsonwebtoken::Algorithm, JwkKeySet};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let keys = JwkKeySet::from_slice(br#"{"keys": [
+    ///     {"kty": "oct", "kid": "2024-01", "alg": "HS256", "k": "czNjcjN0"}
+    /// ]}"#).unwrap();
+    ///
+    /// assert!(keys.find(Some("2024-01"), Algorithm::HS256).await.is_ok());
+    /// assert!(keys.find(Some("2024-01"), Algorithm::HS512).await.is_err());
+    /// assert!(keys.find(Some("1999-12"), Algorithm::HS256).await.is_err());
+    /// # }
+    /// ```
+    pub fn from_slice(jwks: &[u8]) -> Result<Self, JwksError> {
+        let set = Self::new(JwksSource::Static);
+        set.store(parse_jwks(jwks)?);
+        Ok(set)
+    }
+
+    /// Creates a key set from a JWKS document on disk, which is loaded straight away and reloaded once stale
+    pub fn from_file(path: impl Into<PathBuf>) -> Result<Self, JwksError> {
+        let path = path.into();
+        let keys = parse_jwks(&std::fs::read(&path)?)?;
+        let set = Self::new(JwksSource::File(path));
+        set.store(keys);
+        Ok(set)
+    }
+
+    /// Creates a key set from a JWKS document at a URL, which is fetched on first use and refetched once stale
+    ///
+    /// Use [JwkKeySet::refresh] to fetch keys up-front, e.g. to fail fast on startup. Fetches give up after 10 seconds, or 5 seconds if a connection can't be made; use [JwkKeySet::from_url_with_client] to change this. Documents over 256 KiB are refused with [JwksError::TooLarge] without being read any further.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the TLS backend can't be initialized, like [reqwest::Client::new].
+    pub fn from_url(url: impl Into<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(5))
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("couldn't create key set client");
+        Self::from_url_with_client(url, client)
+    }
+
+    /// Creates a key set from a JWKS document at a URL which is fetched using `client`, e.g. to use custom timeouts, proxies or root certificates
+    pub fn from_url_with_client(url: impl Into<String>, client: reqwest::Client) -> Self {
+        Self::new(JwksSource::Url(client, url.into()))
+    }
+
+    /// Sets how long loaded keys are used for before being reloaded, defaulting to an hour
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the minimum time between reloads caused by unknown `kid`s, defaulting to 30 seconds
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Reloads keys from this set's file or URL straight away
+    ///
+    /// Key sets created using [JwkKeySet::from_slice] have nowhere to reload from, so this does nothing for them.
+    pub async fn refresh(&self) -> Result<(), JwksError> {
+        self.cache.write().unwrap().attempted = Some(Instant::now());
+        let jwks = match self.source.as_ref() {
+            JwksSource::Static => return Ok(()),
+            JwksSource::File(path) => tokio::fs::read(path).await?,
+            JwksSource::Url(client, url) => fetch(client, url).await?,
+        };
+        self.store(parse_jwks(&jwks)?);
+        Ok(())
+    }
+
+    /// Finds the key for a token with a `kid` header which was signed using `algorithm`
+    ///
+    /// Tokens without a `kid` are only accepted if this set contains a single key. If no key can be found even after reloading, this gives [AuthError::InvalidToken], or [AuthError::Unavailable] if keys couldn't be loaded at all.
+    pub async fn find(
+        &self,
+        kid: Option<&str>,
+        algorithm: Algorithm,
+    ) -> Result<DecodingKey, AuthError> {
+        // Reload stale keys, carrying on with the old ones if that fails
+        let (stale, cooled) = self.staleness();
+        if stale && cooled {
+            let _ = self.refresh().await;
+        }
+
+        // Look the key up, reloading if it's unknown and there's been no recent reload
+        if let Some(key) = self.lookup(kid, algorithm) {
+            return Ok(key);
+        }
+        if !stale && cooled {
+            let _ = self.refresh().await;
+            if let Some(key) = self.lookup(kid, algorithm) {
+                return Ok(key);
+            }
+        }
+
+        // Only blame the token if there were keys to check against
+        match self.cache.read().unwrap().loaded {
+            Some(_) => Err(AuthError::InvalidToken),
+            None => Err(AuthError::Unavailable),
+        }
+    }
+
+    fn new(source: JwksSource) -> Self {
+        Self {
+            source: Arc::new(source),
+            ttl: Duration::from_secs(60 * 60),
+            cooldown: Duration::from_secs(30),
+            cache: Arc::default(),
+        }
+    }
+
+    /// Replaces the cached keys with newly loaded ones
+    fn store(&self, keys: Vec<CachedKey>) {
+        let mut cache = self.cache.write().unwrap();
+        cache.keys = keys;
+        cache.loaded = Some(Instant::now());
+    }
+
+    /// Checks if keys are past their ttl and if the cooldown since the last reload attempt has passed
+    fn staleness(&self) -> (bool, bool) {
+        if matches!(self.source.as_ref(), JwksSource::Static) {
+            return (false, false);
+        }
+        let cache = self.cache.read().unwrap();
+        let stale = cache
+            .loaded
+            .map_or(true, |loaded| loaded.elapsed() >= self.ttl);
+        let cooled = cache
+            .attempted
+            .map_or(true, |attempted| attempted.elapsed() >= self.cooldown);
+        (stale, cooled)
+    }
+
+    /// Looks up a cached key which can be used to check a signature made using `algorithm`
+    fn lookup(&self, kid: Option<&str>, algorithm: Algorithm) -> Option<DecodingKey> {
+        let cache = self.cache.read().unwrap();
+        let key = match kid {
+            Some(kid) => cache
+                .keys
+                .iter()
+                .find(|key| key.kid.as_deref() == Some(kid)),
+            None if cache.keys.len() == 1 => cache.keys.first(),
+            None => None,
+        }?;
+        key.algorithms.contains(&algorithm).then(|| key.key.clone())
+    }
+}
+
+impl fmt::Debug for JwkKeySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cache = self.cache.read().unwrap();
+        let kids: Vec<_> = cache.keys.iter().map(|key| key.kid.as_deref()).collect();
+        f.debug_struct("JwkKeySet")
+            .field("kids", &kids)
+            .field("ttl", &self.ttl)
+            .field("cooldown", &self.cooldown)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Error given off when a key set couldn't be loaded
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum JwksError {
+    /// The key set's file couldn't be read
+    Io(std::io::Error),
+    /// The key set's URL couldn't be fetched
+    Http(reqwest::Error),
+    /// The key set wasn't a valid JWKS document
+    Json(serde_json::Error),
+    /// The key set fetched was larger than a JWKS document should ever be
+    TooLarge,
+}
+
+impl fmt::Display for JwksError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Couldn't read key set, {}", err),
+            Self::Http(err) => write!(f, "Couldn't fetch key set, {}", err),
+            Self::Json(err) => write!(f, "Invalid key set, {}", err),
+            Self::TooLarge => write!(f, "Key set is too large"),
+        }
+    }
+}
+
+impl std::error::Error for JwksError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Http(err) => Some(err),
+            Self::Json(err) => Some(err),
+            Self::TooLarge => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for JwksError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<reqwest::Error> for JwksError {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Http(err)
+    }
+}
+
+impl From<serde_json::Error> for JwksError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+/// Parses a JWKS document, skipping over any keys which can't be used for checking signatures
+fn parse_jwks(jwks: &[u8]) -> Result<Vec<CachedKey>, JwksError> {
+    #[derive(Deserialize)]
+    struct RawJwkSet {
+        keys: Vec<serde_json::Value>,
+    }
+
+    let raw: RawJwkSet = serde_json::from_slice(jwks)?;
+    Ok(raw
+        .keys
+        .into_iter()
+        .filter_map(|value| serde_json::from_value::<Jwk>(value).ok())
+        .filter(|jwk| !matches!(jwk.common.public_key_use, Some(PublicKeyUse::Encryption)))
+        .filter_map(|jwk| {
+            Some(CachedKey {
+                algorithms: key_algorithms(&jwk)?,
+                key: DecodingKey::from_jwk(&jwk).ok()?,
+                kid: jwk.common.key_id,
+            })
+        })
+        .collect())
+}
+
+/// Fetches a JWKS document from `url`, giving up as soon as it's known to be over [MAX_JWKS_LEN]
+async fn fetch(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, JwksError> {
+    let mut res = client.get(url).send().await?.error_for_status()?;
+    if res.content_length().unwrap_or_default() > MAX_JWKS_LEN as u64 {
+        return Err(JwksError::TooLarge);
+    }
+    let mut jwks = Vec::new();
+    while let Some(chunk) = res.chunk().await? {
+        if jwks.len() + chunk.len() > MAX_JWKS_LEN {
+            return Err(JwksError::TooLarge);
+        }
+        jwks.extend_from_slice(&chunk);
+    }
+    Ok(jwks)
+}
+
+/// Gets the algorithms a key can be used with, going by its `alg` if present or otherwise its type and curve
+fn key_algorithms(jwk: &Jwk) -> Option<Vec<Algorithm>> {
+    use Algorithm::*;
+
+    let family: &[Algorithm] = match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => &[RS256, RS384, RS512, PS256, PS384, PS512],
+        AlgorithmParameters::EllipticCurve(params) => match params.curve {
+            EllipticCurve::P256 => &[ES256],
+            EllipticCurve::P384 => &[ES384],
+            _ => return None,
+        },
+        AlgorithmParameters::OctetKeyPair(_) => &[EdDSA],
+        AlgorithmParameters::OctetKey(_) => &[HS256, HS384, HS512],
+    };
+    match jwk.common.key_algorithm {
+        Some(alg) => {
+            let alg = alg.to_string().parse().ok()?;
+            family.contains(&alg).then(|| vec![alg])
+        }
+        None => Some(family.to_vec()),
+    }
+}
@@ -0,0 +1,302 @@
+#[cfg(feature = "auth-bearer")]
+use crate::auth_bearer::{has_access_token, make_private, QueryTokenAllowed};
+use crate::AuthRejection;
+use axum_core::{
+    extract::FromRequestParts,
+    response::{IntoResponse, Response},
+};
+use http::Request;
+use std::{
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Middleware layer which requires every request to pass an authentication extractor before reaching the inner service
+///
+/// Requests are run through the extractor `E`, which can be any extractor in this crate, using a router state `S`. Failures are rejected with the extractor's usual [AuthRejection] and challenge. Successes have the extractor put into their extensions, so handlers can get at the authenticated principal using axum's `Extension` extractor without checking credentials again.
+///
+/// Using this with `Router::route_layer` protects every route on a router in one go whilst still giving `404 Not Found` for unknown paths. An [AuthConfig](crate::AuthConfig) is picked up as usual, as long as its `Extension` layer is added after this one so that it runs first. Bearer and API key extractors only read credentials from the query behind this or a [BearerQueryLayer], so responses to requests with an `access_token` query parameter, or whose API key was read from the query, are made `Cache-Control: private`, dropping any `public` directive, whichever extractor ends up reading the token.
+///
+/// # Example
+///
+/// ```
+/// use axum::{body::Body, http::{Request, StatusCode}, routing::get, Extension, Router};
+/// use axum_auth::{AuthBearer, RequireAuthLayer};
+/// use tower::ServiceExt;
+///
+/// /// Handler which is only reached with a bearer token
+/// async fn handler(Extension(AuthBearer(token)): Extension<AuthBearer>) -> String {
+///     format!("Found a bearer token: {}", token.expose_secret())
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let app: Router = Router::new()
+///     .route("/", get(handler))
+///     .route("/other", get(handler))
+///     .route_layer(RequireAuthLayer::<AuthBearer>::new());
+///
+/// let res = app.oneshot(Request::new(Body::empty())).await.unwrap();
+/// assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+/// # }
+/// ```
+///
+/// Extractors which need router state, such as [ValidatedBearer](crate::ValidatedBearer), are given it using [RequireAuthLayer::with_state]:
+///
+/// ```no_run
+/// use async_trait::async_trait;
+/// use axum::{routing::get, Extension, Router};
+/// use axum_auth::{AuthError, BearerValidator, RequireAuthLayer, ValidatedBearer};
+///
+/// /// Validator which knows about a single service's token
+/// #[derive(Clone)]
+/// struct Services;
+///
+/// #[async_trait]
+/// impl BearerValidator for Services {
+///     type Principal = String;
+///
+///     async fn validate(&self, token: &str) -> Result<String, AuthError> {
+///         // NOTE: compare tokens in constant time outside of examples!
+///         match token {
+///             "s3cr3t" => Ok("billing".to_string()),
+///             _ => Err(AuthError::InvalidToken),
+///         }
+///     }
+/// }
+///
+/// /// Handler which only runs for validated services
+/// async fn handler(Extension(ValidatedBearer(service)): Extension<ValidatedBearer<Services>>) -> String {
+///     format!("Hello, {} service", service)
+/// }
+///
+/// let app: Router = Router::new()
+///     .route("/", get(handler))
+///     .route_layer(RequireAuthLayer::<ValidatedBearer<Services>, _>::with_state(Services));
+/// ```
+pub struct RequireAuthLayer<E, S = ()> {
+    state: S,
+    _extractor: PhantomData<fn() -> E>,
+}
+
+impl<E> RequireAuthLayer<E> {
+    /// Creates a new layer for an extractor which doesn't need any router state
+    pub fn new() -> Self {
+        Self::with_state(())
+    }
+}
+
+impl<E> Default for RequireAuthLayer<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E, S> RequireAuthLayer<E, S> {
+    /// Creates a new layer which gives the extractor `state`, e.g. a [BearerValidator](crate::BearerValidator)
+    pub fn with_state(state: S) -> Self {
+        Self {
+            state,
+            _extractor: PhantomData,
+        }
+    }
+}
+
+impl<E, S: Clone> Clone for RequireAuthLayer<E, S> {
+    fn clone(&self) -> Self {
+        Self::with_state(self.state.clone())
+    }
+}
+
+impl<E, S: fmt::Debug> fmt::Debug for RequireAuthLayer<E, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequireAuthLayer")
+            .field("extractor", &std::any::type_name::<E>())
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<T, E, S: Clone> Layer<T> for RequireAuthLayer<E, S> {
+    type Service = RequireAuth<T, E, S>;
+
+    fn layer(&self, inner: T) -> Self::Service {
+        RequireAuth {
+            inner,
+            state: self.state.clone(),
+            _extractor: PhantomData,
+        }
+    }
+}
+
+/// Middleware service which requires every request to pass an authentication extractor, see [RequireAuthLayer]
+pub struct RequireAuth<T, E, S = ()> {
+    inner: T,
+    state: S,
+    _extractor: PhantomData<fn() -> E>,
+}
+
+impl<T: Clone, E, S: Clone> Clone for RequireAuth<T, E, S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            state: self.state.clone(),
+            _extractor: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug, E, S: fmt::Debug> fmt::Debug for RequireAuth<T, E, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequireAuth")
+            .field("inner", &self.inner)
+            .field("extractor", &std::any::type_name::<E>())
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<T, E, S, B> Service<Request<B>> for RequireAuth<T, E, S>
+where
+    T: Service<Request<B>, Response = Response> + Clone + Send + 'static,
+    T::Future: Send,
+    E: FromRequestParts<S, Rejection = AuthRejection> + Clone + Send + Sync + 'static,
+    S: Clone + Send + Sync + 'static,
+    B: Send + 'static,
+{
+    type Response = Response;
+    type Error = T::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, T::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        // Take the service which was polled ready, leaving a fresh clone in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let state = self.state.clone();
+
+        Box::pin(async move {
+            // Authenticate then hand the extractor over to the inner service
+            let (mut parts, body) = req.into_parts();
+            #[cfg(feature = "auth-bearer")]
+            let query_token = parts.uri.query().is_some_and(has_access_token);
+            #[cfg(feature = "auth-bearer")]
+            let allowed = QueryTokenAllowed::default();
+            #[cfg(feature = "auth-bearer")]
+            parts.extensions.insert(allowed.clone());
+            match E::from_request_parts(&mut parts, &state).await {
+                Ok(auth) => {
+                    parts.extensions.insert(auth);
+                    let res = inner.call(Request::from_parts(parts, body)).await;
+
+                    // Stop shared caches from storing responses to tokens or keys in the query, as RFC 6750 asks, whichever extractor reads them
+                    #[cfg(feature = "auth-bearer")]
+                    let res = res.map(|mut res| {
+                        if query_token || allowed.is_marked() {
+                            make_private(res.headers_mut());
+                        }
+                        res
+                    });
+                    res
+                }
+                Err(rejection) => Ok(rejection.into_response()),
+            }
+        })
+    }
+}
+
+/// Middleware layer which lets [AuthBearer](crate::AuthBearer) read tokens from the query by making responses to them private
+///
+/// This is enabled via the `auth-bearer` feature.
+///
+/// RFC 6750 asks for responses to requests with an `access_token` query parameter to be marked `Cache-Control: private`, so that shared caches don't hand them out to anyone else. Even with [AuthConfig::bearer_query](crate::AuthConfig::bearer_query) turned on, bearer extractors only read tokens from the query on routes behind this or a [RequireAuthLayer], which both add that directive and drop any `public` one. API key extractors reading keys from the query, like `AuthApiKey<ApiKeyQuery>`, are held to the same rule. Use this for routes where handlers extract tokens or keys themselves.
+///
+/// # Example
+///
+/// ```
+/// use axum::{body::Body, http::Request, routing::get, Extension, Router};
+/// use axum_auth::{AuthBearer, AuthConfig, BearerQueryLayer};
+/// use tower::ServiceExt;
+///
+/// async fn handler(AuthBearer(token): AuthBearer) -> String {
+///     format!("Found a bearer token: {}", token.expose_secret())
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let app: Router = Router::new()
+///     .route("/", get(handler))
+///     .layer(Extension(AuthConfig::new().bearer_query(true)))
+///     .layer(BearerQueryLayer::new());
+///
+/// let req = Request::get("/?access_token=s3cr3t").body(Body::empty()).unwrap();
+/// let res = app.oneshot(req).await.unwrap();
+/// assert_eq!(res.headers()["Cache-Control"], "private");
+/// # }
+/// ```
+#[cfg(feature = "auth-bearer")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BearerQueryLayer;
+
+#[cfg(feature = "auth-bearer")]
+impl BearerQueryLayer {
+    /// Creates a new layer
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[cfg(feature = "auth-bearer")]
+impl<T> Layer<T> for BearerQueryLayer {
+    type Service = BearerQuery<T>;
+
+    fn layer(&self, inner: T) -> Self::Service {
+        BearerQuery { inner }
+    }
+}
+
+/// Middleware service which lets bearer tokens be read from the query by making responses to them private, see [BearerQueryLayer]
+#[cfg(feature = "auth-bearer")]
+#[derive(Debug, Clone)]
+pub struct BearerQuery<T> {
+    inner: T,
+}
+
+#[cfg(feature = "auth-bearer")]
+impl<T, B, ResBody> Service<Request<B>> for BearerQuery<T>
+where
+    T: Service<Request<B>, Response = http::Response<ResBody>>,
+    T::Future: Send + 'static,
+{
+    type Response = http::Response<ResBody>;
+    type Error = T::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, T::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        // Let extractors read tokens from the query, making responses private if there's one there or a key was read from it
+        let query_token = req.uri().query().is_some_and(has_access_token);
+        let allowed = QueryTokenAllowed::default();
+        req.extensions_mut().insert(allowed.clone());
+        let future = self.inner.call(req);
+
+        Box::pin(async move {
+            let mut res = future.await?;
+            if query_token || allowed.is_marked() {
+                make_private(res.headers_mut());
+            }
+            Ok(res)
+        })
+    }
+}
@@ -4,20 +4,160 @@
 //!
 //! Check out the following structures for more item-level documentation:
 //!
-//! - Basic auth: [AuthBasic]
-//! - Bearer auth: [AuthBearer]
+//! - Basic auth: [AuthBasic], or [AuthBasicRaw] for credentials in an unknown character set
+//! - Bearer auth: [AuthBearer], or [AuthBearerBody] for tokens in form bodies
+//! - Secrets: [Secret], which passwords and tokens are wrapped in so they're redacted from logs and wiped from memory
+//! - Basic or bearer auth: [AuthAny], for routes accepting either
+//! - Proxy auth: [ProxyAuthBasic] and [ProxyAuthBearer], reading `Proxy-Authorization` for forward proxies
+//! - Verified basic auth: [VerifiedBasic], using a [BasicVerifier] from your router's state
+//! - Htpasswd files: [VerifiedBasic] with an `HtpasswdVerifier` from your router's state (requires the `auth-htpasswd` feature)
+//! - Password hashes: `PasswordPolicy`, for checking and upgrading Argon2id, scrypt and PBKDF2 hashes inside of your own [BasicVerifier] (requires the `auth-password` feature)
+//! - Validated bearer auth: [ValidatedBearer], using a [BearerValidator] from your router's state
+//! - Fixed credentials: [StaticBasicCredentials] and [StaticBearerToken], which check credentials in constant time, as does [constant_time_eq] for your own verifiers
+//! - JSON Web Tokens: `AuthJwt`, using a `JwtDecoder` from your router's state (requires the `auth-jwt` feature), which can pick keys out of a `JwkKeySet` (requires the `auth-jwks` feature)
+//! - Digest auth: `AuthDigest` and `AuthDigestBody`, using a `DigestAuth` from your router's state (requires the `auth-digest` feature)
+//! - API keys: `AuthApiKey` and `ValidatedApiKey`, reading keys from a header, query parameter or cookie picked by an `ApiKeySource` (requires the `auth-api-key` feature)
+//! - Opaque bearer tokens: [ValidatedBearer] with an `Introspector` from your router's state, giving an `IntrospectedToken` (requires the `auth-introspection` feature)
+//! - Session cookies: `AuthSession`, reading typed data out of a signed or encrypted cookie issued and cleared using `SessionCookies` from your router's state (requires the `auth-session` feature)
+//! - Server-side sessions: `AuthStoredSession`, looking up sessions by an opaque identifier from a cookie or bearer token in a `SessionStore` such as a `MemoryStore` or `FileStore`, using `StoredSessions` from your router's state (requires the `auth-session` feature)
+//! - Scopes: [RequireScopes], wrapping any of the above whose principal implements [HasScopes] so routes can require scopes, rejecting tokens without them as `403 Forbidden`
+//! - Policies: [Authorized], wrapping any of the above to check a [Policy] built out of roles, claims and the request itself, rejecting principals it doesn't let through as `403 Forbidden` with the reason why
+//! - Optional auth: [OptionalAuth], wrapping any of the above so requests without credentials are let through whilst bad credentials are still rejected
+//! - Protecting whole routers: [RequireAuthLayer], running any of the above before requests reach your handlers
+//! - Query tokens: [BearerQueryLayer], letting bearer tokens and API keys be read from the query on routes which check them in handlers by making responses to them private
+//!
+//! All of these reject requests with an [AuthRejection], which is sent as `401 Unauthorized` along with a `WWW-Authenticate` challenge. The [AuthError] inside can be matched on to find out exactly what went wrong, and an [AuthConfig] can be used to change the realm or status codes.
 //!
 //! That's all there is to it!
 
-#[cfg(not(any(feature = "auth-basic", feature = "auth-bearer")))]
+#[cfg(not(any(
+    feature = "auth-basic",
+    feature = "auth-bearer",
+    feature = "auth-digest",
+    feature = "auth-password",
+    feature = "auth-session"
+)))]
 compile_error!(r#"At least one feature must be enabled!"#);
 
+#[cfg(all(feature = "auth-basic", feature = "auth-bearer"))]
+mod auth_any;
+#[cfg(feature = "auth-api-key")]
+mod auth_api_key;
 #[cfg(feature = "auth-basic")]
 mod auth_basic;
 #[cfg(feature = "auth-bearer")]
 mod auth_bearer;
+#[cfg(feature = "auth-digest")]
+mod auth_digest;
+#[cfg(feature = "auth-jwt")]
+mod auth_jwt;
+mod config;
+mod error;
+mod header;
+#[cfg(feature = "auth-htpasswd")]
+mod htpasswd;
+#[cfg(feature = "auth-introspection")]
+mod introspection;
+#[cfg(feature = "auth-jwks")]
+mod jwks;
+mod layer;
+mod optional;
+#[cfg(feature = "auth-password")]
+mod password;
+mod policy;
+mod rejection;
+#[cfg(feature = "auth-bearer")]
+mod scope;
+#[cfg(any(
+    feature = "auth-basic",
+    feature = "auth-bearer",
+    feature = "auth-session"
+))]
+mod secret;
+#[cfg(feature = "auth-session")]
+mod session;
+#[cfg(feature = "auth-session")]
+mod store;
+#[cfg(any(
+    feature = "auth-basic",
+    feature = "auth-bearer",
+    feature = "auth-session"
+))]
+mod verify;
 
+#[cfg(all(feature = "auth-basic", feature = "auth-bearer"))]
+pub use auth_any::AuthAny;
+#[cfg(feature = "auth-api-key")]
+pub use auth_api_key::{
+    ApiKeyCookie, ApiKeyLocation, ApiKeyQuery, ApiKeySource, AuthApiKey, ValidatedApiKey, XApiKey,
+};
+#[cfg(feature = "auth-basic")]
+pub use auth_basic::{AuthBasic, AuthBasicRaw, BasicCharset, ProxyAuthBasic, VerifiedBasic};
+#[cfg(feature = "auth-bearer")]
+pub use auth_bearer::{AuthBearer, AuthBearerBody, ProxyAuthBearer, ValidatedBearer};
+#[cfg(feature = "auth-digest")]
+pub use auth_digest::{
+    AuthDigest, AuthDigestBody, DigestAlgorithm, DigestAuth, DigestCredentials, DigestSecret, Qop,
+};
+#[cfg(feature = "auth-jwt")]
+pub use auth_jwt::{AuthJwt, JwtDecoder};
+pub use config::AuthConfig;
+pub use error::AuthError;
+#[cfg(feature = "auth-htpasswd")]
+pub use htpasswd::HtpasswdVerifier;
+#[cfg(feature = "auth-introspection")]
+pub use introspection::{IntrospectedToken, Introspector};
+#[cfg(feature = "auth-jwks")]
+pub use jwks::{JwkKeySet, JwksError};
+#[cfg(feature = "auth-bearer")]
+pub use layer::{BearerQuery, BearerQueryLayer};
+pub use layer::{RequireAuth, RequireAuthLayer};
+pub use optional::OptionalAuth;
+#[cfg(feature = "auth-password")]
+pub use password::{PasswordError, PasswordPolicy};
+pub use policy::{
+    all, any, has_claim, has_role, not, All, Any, Authorized, HasClaim, HasClaims, HasRole,
+    HasRoles, Not, Policy,
+};
+pub use rejection::{AuthRejection, Challenge};
+#[cfg(feature = "auth-bearer")]
+pub use scope::{HasScopes, RequireScopes, RequiredScopes};
+#[cfg(any(
+    feature = "auth-basic",
+    feature = "auth-bearer",
+    feature = "auth-session"
+))]
+pub use secret::Secret;
+#[cfg(feature = "auth-session")]
+pub use session::{AuthSession, SameSite, SessionCookie, SessionCookies, SessionError};
+#[cfg(feature = "auth-session")]
+pub use store::{
+    AuthStoredSession, FileStore, MemoryStore, SessionIdSource, SessionStore, StoredSessions,
+};
+#[cfg(any(
+    feature = "auth-basic",
+    feature = "auth-bearer",
+    feature = "auth-session"
+))]
+pub use verify::constant_time_eq;
 #[cfg(feature = "auth-basic")]
-pub use auth_basic::AuthBasic;
+pub use verify::{BasicVerifier, StaticBasicCredentials};
 #[cfg(feature = "auth-bearer")]
-pub use auth_bearer::AuthBearer;
+pub use verify::{BearerValidator, StaticBearerToken};
+
+/// Re-export of the [argon2](https://docs.rs/argon2) crate for picking a [PasswordPolicy]'s parameters
+#[cfg(feature = "auth-password")]
+pub use argon2;
+/// Re-export of the [pbkdf2](https://docs.rs/pbkdf2) crate for picking a [PasswordPolicy]'s parameters
+#[cfg(feature = "auth-password")]
+pub use pbkdf2;
+/// Re-export of the [scrypt](https://docs.rs/scrypt) crate for picking a [PasswordPolicy]'s parameters
+#[cfg(feature = "auth-password")]
+pub use scrypt;
+
+/// Re-export of the [jsonwebtoken](https://docs.rs/jsonwebtoken) crate used for decoding tokens
+#[cfg(feature = "auth-jwt")]
+pub use jsonwebtoken;
+/// Re-export of the [reqwest](https://docs.rs/reqwest) crate for configuring the HTTP clients used to fetch keys and introspect tokens
+#[cfg(any(feature = "auth-introspection", feature = "auth-jwks"))]
+pub use reqwest;
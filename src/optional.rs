@@ -0,0 +1,63 @@
+use crate::AuthRejection;
+use async_trait::async_trait;
+use axum_core::extract::FromRequestParts;
+use http::request::Parts;
+
+/// Optional authentication extractor which gives [None] when no credentials were sent, but still rejects bad ones
+///
+/// Wrapping an extractor from this crate in a plain [Option] hides every error, so a malformed or invalid header would quietly be treated the same as a missing one. This only lets requests through anonymously if the wrapped extractor `T` failed because credentials were [missing](crate::AuthError::is_missing), which makes it a good fit for public endpoints that personalise responses for logged-in users.
+///
+/// # Example
+///
+/// ```
+/// use axum::{extract::FromRequestParts, http::Request};
+/// use axum_auth::{AuthBearer, OptionalAuth};
+///
+/// /// Handler which greets users differently if they're logged in
+/// async fn handler(OptionalAuth(auth): OptionalAuth<AuthBearer>) -> String {
+///     match auth {
+///         Some(AuthBearer(token)) => format!("Welcome back, {}", token.expose_secret()),
+///         None => "Hello, stranger".to_string(),
+///     }
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// // Anonymous requests are let through
+/// let (mut parts, _) = Request::new(()).into_parts();
+/// let OptionalAuth(auth) = OptionalAuth::<AuthBearer>::from_request_parts(&mut parts, &()).await.unwrap();
+/// assert_eq!(auth, None);
+///
+/// // Malformed credentials are still rejected
+/// let (mut parts, _) = Request::builder()
+///     .header("Authorization", "Bearer ")
+///     .body(())
+///     .unwrap()
+///     .into_parts();
+/// assert!(OptionalAuth::<AuthBearer>::from_request_parts(&mut parts, &()).await.is_err());
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// This gives off every error the wrapped extractor does, apart from the ones meaning credentials were missing.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct OptionalAuth<T>(pub Option<T>);
+
+#[async_trait]
+impl<S, T> FromRequestParts<S> for OptionalAuth<T>
+where
+    S: Send + Sync,
+    T: FromRequestParts<S, Rejection = AuthRejection>,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Only let requests without any credentials through
+        match T::from_request_parts(parts, state).await {
+            Ok(auth) => Ok(Self(Some(auth))),
+            Err(rejection) if rejection.error().is_missing() => Ok(Self(None)),
+            Err(rejection) => Err(rejection),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use argon2::Argon2;
+use password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, SaltString};
+use pbkdf2::Pbkdf2;
+use scrypt::Scrypt;
+use std::fmt;
+
+/// Policy for hashing passwords, used to hash new passwords as well as to check stored ones
+///
+/// This is enabled via the `auth-password` feature.
+///
+/// Hashes are stored as PHC strings such as `$argon2id$v=19$m=19456,t=2,p=1$...`, which carry their algorithm and parameters with them. Stored hashes made using Argon2, scrypt or PBKDF2 can always be verified, whilst new hashes are made using the algorithm and parameters this policy was created with. Stored hashes which don't match those can be found using [PasswordPolicy::needs_rehash], so they can be upgraded the next time their user logs in.
+///
+/// Hashing is slow on purpose, so consider calling these from tokio's `spawn_blocking` in busy servers.
+///
+/// # Example
+///
+/// ```
+/// use axum_auth::{pbkdf2, PasswordPolicy};
+///
+/// // Hash made a while back using PBKDF2
+/// let old = PasswordPolicy::pbkdf2(pbkdf2::Params { rounds: 1000, output_length: 32 });
+/// let stored = old.hash("hunter2").unwrap();
+///
+/// // Passwords are checked against it just fine, but it's due an upgrade to Argon2id
+/// let policy = PasswordPolicy::default();
+/// assert!(policy.verify("hunter2", &stored).unwrap());
+/// assert!(!policy.verify("hunter3", &stored).unwrap());
+/// assert!(policy.needs_rehash(&stored));
+///
+/// let upgraded = policy.hash("hunter2").unwrap();
+/// assert!(upgraded.starts_with("$argon2id$"));
+/// assert!(!policy.needs_rehash(&upgraded));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    algorithm: PolicyAlgorithm,
+}
+
+/// Algorithm new passwords are hashed with, along with its parameters
+#[derive(Debug, Clone)]
+enum PolicyAlgorithm {
+    Argon2id(argon2::Params),
+    Scrypt(scrypt::Params),
+    Pbkdf2(pbkdf2::Params),
+}
+
+impl PasswordPolicy {
+    /// Creates a policy which hashes passwords using Argon2id with the given parameters
+    pub fn argon2id(params: argon2::Params) -> Self {
+        Self::new(PolicyAlgorithm::Argon2id(params))
+    }
+
+    /// Creates a policy which hashes passwords using scrypt with the given parameters
+    pub fn scrypt(params: scrypt::Params) -> Self {
+        Self::new(PolicyAlgorithm::Scrypt(params))
+    }
+
+    /// Creates a policy which hashes passwords using PBKDF2 with HMAC-SHA256 and the given parameters
+    pub fn pbkdf2(params: pbkdf2::Params) -> Self {
+        Self::new(PolicyAlgorithm::Pbkdf2(params))
+    }
+
+    fn new(algorithm: PolicyAlgorithm) -> Self {
+        Self { algorithm }
+    }
+
+    /// Hashes a password with a random salt, giving a PHC string to store
+    pub fn hash(&self, password: &str) -> Result<String, PasswordError> {
+        let password = password.as_bytes();
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = match &self.algorithm {
+            PolicyAlgorithm::Argon2id(params) => Argon2::new(
+                argon2::Algorithm::Argon2id,
+                argon2::Version::V0x13,
+                params.clone(),
+            )
+            .hash_password(password, &salt),
+            PolicyAlgorithm::Scrypt(params) => {
+                Scrypt.hash_password_customized(password, None, None, *params, &salt)
+            }
+            PolicyAlgorithm::Pbkdf2(params) => Pbkdf2.hash_password_customized(
+                password,
+                Some(pbkdf2::Algorithm::Pbkdf2Sha256.ident()),
+                None,
+                *params,
+                &salt,
+            ),
+        };
+        hash.map(|hash| hash.to_string())
+            .map_err(PasswordError::Hashing)
+    }
+
+    /// Checks a password against a stored PHC string in constant time, giving whether it matches
+    ///
+    /// Stored hashes made using any of the supported algorithms are checked using their own parameters, not just ones matching this policy.
+    pub fn verify(&self, password: &str, hash: &str) -> Result<bool, PasswordError> {
+        let hash = PasswordHash::new(hash).map_err(PasswordError::InvalidHash)?;
+        match hash.verify_password(&[&Argon2::default(), &Scrypt, &Pbkdf2], password) {
+            Ok(()) => Ok(true),
+            Err(password_hash::Error::Password) => Ok(false),
+            Err(err) => Err(PasswordError::InvalidHash(err)),
+        }
+    }
+
+    /// Checks if a stored PHC string was made using another algorithm or other parameters than this policy's, meaning it should be replaced with a fresh [hash](PasswordPolicy::hash)
+    ///
+    /// Stored hashes which can't be parsed always need rehashing.
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        let Ok(hash) = PasswordHash::new(hash) else {
+            return true;
+        };
+        let output_len = hash.hash.map(|output| output.len());
+        match &self.algorithm {
+            PolicyAlgorithm::Argon2id(params) => {
+                let expected_len = params
+                    .output_len()
+                    .unwrap_or(argon2::Params::DEFAULT_OUTPUT_LEN);
+                hash.algorithm != argon2::Algorithm::Argon2id.ident()
+                    || hash.version != Some(argon2::Version::V0x13.into())
+                    || output_len != Some(expected_len)
+                    || argon2::Params::try_from(&hash).map_or(true, |stored| {
+                        stored.m_cost() != params.m_cost()
+                            || stored.t_cost() != params.t_cost()
+                            || stored.p_cost() != params.p_cost()
+                    })
+            }
+            PolicyAlgorithm::Scrypt(params) => {
+                hash.algorithm != scrypt::ALG_ID
+                    || scrypt::Params::try_from(&hash).map_or(true, |stored| {
+                        stored.log_n() != params.log_n()
+                            || stored.r() != params.r()
+                            || stored.p() != params.p()
+                    })
+            }
+            PolicyAlgorithm::Pbkdf2(params) => {
+                hash.algorithm != pbkdf2::Algorithm::Pbkdf2Sha256.ident()
+                    || output_len != Some(params.output_length)
+                    || pbkdf2::Params::try_from(&hash)
+                        .map_or(true, |stored| stored.rounds != params.rounds)
+            }
+        }
+    }
+}
+
+impl Default for PasswordPolicy {
+    /// Creates a policy which hashes passwords using Argon2id with its recommended parameters
+    fn default() -> Self {
+        Self::argon2id(argon2::Params::DEFAULT)
+    }
+}
+
+/// Error given off when a password couldn't be hashed or checked
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PasswordError {
+    /// The stored hash wasn't a PHC string made using a supported algorithm
+    InvalidHash(password_hash::Error),
+    /// The password couldn't be hashed, e.g. because the policy's parameters are out of range
+    Hashing(password_hash::Error),
+}
+
+impl fmt::Display for PasswordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHash(err) => write!(f, "Invalid password hash, {}", err),
+            Self::Hashing(err) => write!(f, "Couldn't hash password, {}", err),
+        }
+    }
+}
+
+impl std::error::Error for PasswordError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidHash(err) | Self::Hashing(err) => Some(err),
+        }
+    }
+}
@@ -0,0 +1,523 @@
+use crate::{AuthConfig, AuthError, AuthRejection};
+use async_trait::async_trait;
+use axum_core::extract::{FromRef, FromRequestParts};
+use http::{request::Parts, StatusCode};
+use std::{borrow::Cow, fmt, marker::PhantomData};
+
+/// Rule deciding whether an authenticated principal `P` may go ahead with a request
+///
+/// The principal is whatever extractor authenticated the request, e.g. a [ValidatedBearer](crate::ValidatedBearer) or [VerifiedBasic](crate::VerifiedBasic), so policies can use [HasRoles] and [HasClaims] to look inside of it. The request parts are given mutably so other extractors such as axum's `Path` can be run on them to get at path parameters.
+///
+/// This is implemented for the combinators [all], [any], [not], [has_role] and [has_claim], and for closures or functions taking the principal and the request parts, which is the easiest way to write synchronous checks. Implement it yourself for checks which need to wait on something, like a database of who owns what.
+///
+/// # Combinators
+///
+/// ```
+/// use axum::http::request::Parts;
+/// use axum_auth::{all, any, has_claim, has_role, not, AuthError, HasClaims, HasRoles, Policy};
+/// use std::borrow::Cow;
+///
+/// /// User from our database
+/// struct User {
+///     department: String,
+///     roles: Vec<String>,
+/// }
+///
+/// impl HasRoles for User {
+///     fn has_role(&self, role: &str) -> bool {
+///         self.roles.iter().any(|own| own == role)
+///     }
+/// }
+///
+/// impl HasClaims for User {
+///     fn claim(&self, name: &str) -> Option<Cow<'_, str>> {
+///         (name == "department").then(|| Cow::Borrowed(self.department.as_str()))
+///     }
+/// }
+///
+/// /// Only lets safe methods through
+/// fn read_only(_: &User, parts: &Parts) -> Result<(), AuthError> {
+///     match parts.method.is_safe() {
+///         true => Ok(()),
+///         false => Err(AuthError::forbidden("only reading is allowed")),
+///     }
+/// }
+///
+/// // Admins can do anything, whilst staff who aren't suspended can only read
+/// let policy = any(
+///     has_role("admin"),
+///     all(
+///         all(has_claim("department", "staff"), not(has_role("suspended"))),
+///         read_only,
+///     ),
+/// );
+/// # fn is_policy<P: Policy<User>>(_: &P) {}
+/// # is_policy(&policy);
+/// ```
+///
+/// # Implementing
+///
+/// ```
+/// use async_trait::async_trait;
+/// use axum::{
+///     extract::{FromRequestParts, Path},
+///     http::request::Parts,
+/// };
+/// use axum_auth::{AuthError, Policy, StaticBearerToken, ValidatedBearer};
+///
+/// /// Policy which only lets users at their own account, found in the path as `/users/:id`
+/// #[derive(Clone)]
+/// struct OwnAccount;
+///
+/// #[async_trait]
+/// impl Policy<ValidatedBearer<StaticBearerToken>> for OwnAccount {
+///     async fn check(
+///         &self,
+///         _token: &ValidatedBearer<StaticBearerToken>,
+///         parts: &mut Parts,
+///     ) -> Result<(), AuthError> {
+///         let Path(id) = Path::<u64>::from_request_parts(parts, &())
+///             .await
+///             .map_err(|_| AuthError::forbidden("no account was given"))?;
+///         match id {
+///             1 => Ok(()), // look this up against who the token belongs to
+///             _ => Err(AuthError::forbidden("this isn't your account")),
+///         }
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait Policy<P: Sync + ?Sized>: Send + Sync {
+    /// Checks if `principal` may go ahead with the request, giving off [AuthError::Forbidden] with the reason why if it can't
+    async fn check(&self, principal: &P, parts: &mut Parts) -> Result<(), AuthError>;
+}
+
+#[async_trait]
+impl<P, F> Policy<P> for F
+where
+    P: Sync + ?Sized,
+    F: Fn(&P, &Parts) -> Result<(), AuthError> + Send + Sync,
+{
+    async fn check(&self, principal: &P, parts: &mut Parts) -> Result<(), AuthError> {
+        self(principal, parts)
+    }
+}
+
+/// Authenticated principal which has roles, checked using [has_role]
+///
+/// This is implemented for extractors whose principal implements it, such as a [ValidatedBearer](crate::ValidatedBearer) whose [BearerValidator](crate::BearerValidator) gives back your own user type.
+///
+/// # Example
+///
+/// ```
+/// use axum_auth::HasRoles;
+///
+/// /// User from our database along with the roles they've been given
+/// struct User {
+///     name: String,
+///     roles: Vec<String>,
+/// }
+///
+/// impl HasRoles for User {
+///     fn has_role(&self, role: &str) -> bool {
+///         self.roles.iter().any(|own| own == role)
+///     }
+/// }
+/// ```
+pub trait HasRoles {
+    /// Checks if this has been given `role`
+    fn has_role(&self, role: &str) -> bool;
+}
+
+/// Authenticated principal which has named attributes, checked using [has_claim]
+///
+/// This is implemented for extractors whose principal implements it, such as an [AuthJwt](crate::AuthJwt) with your own claims.
+///
+/// # Example
+///
+/// ```
+/// use axum_auth::HasClaims;
+/// use serde::Deserialize;
+/// use std::borrow::Cow;
+///
+/// /// Claims of a JWT given out to employees
+/// #[derive(Deserialize)]
+/// struct Claims {
+///     sub: String,
+///     department: String,
+/// }
+///
+/// impl HasClaims for Claims {
+///     fn claim(&self, name: &str) -> Option<Cow<'_, str>> {
+///         match name {
+///             "sub" => Some(Cow::Borrowed(&self.sub)),
+///             "department" => Some(Cow::Borrowed(&self.department)),
+///             _ => None,
+///         }
+///     }
+/// }
+/// ```
+pub trait HasClaims {
+    /// Gets the value of the claim called `name`, if there is one
+    fn claim(&self, name: &str) -> Option<Cow<'_, str>>;
+}
+
+#[cfg(feature = "auth-basic")]
+impl<V> HasRoles for crate::VerifiedBasic<V>
+where
+    V: crate::BasicVerifier,
+    V::User: HasRoles,
+{
+    fn has_role(&self, role: &str) -> bool {
+        self.0.has_role(role)
+    }
+}
+
+#[cfg(feature = "auth-basic")]
+impl<V> HasClaims for crate::VerifiedBasic<V>
+where
+    V: crate::BasicVerifier,
+    V::User: HasClaims,
+{
+    fn claim(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.0.claim(name)
+    }
+}
+
+#[cfg(feature = "auth-bearer")]
+impl<V> HasRoles for crate::ValidatedBearer<V>
+where
+    V: crate::BearerValidator,
+    V::Principal: HasRoles,
+{
+    fn has_role(&self, role: &str) -> bool {
+        self.0.has_role(role)
+    }
+}
+
+#[cfg(feature = "auth-bearer")]
+impl<V> HasClaims for crate::ValidatedBearer<V>
+where
+    V: crate::BearerValidator,
+    V::Principal: HasClaims,
+{
+    fn claim(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.0.claim(name)
+    }
+}
+
+#[cfg(feature = "auth-api-key")]
+impl<V, L> HasRoles for crate::ValidatedApiKey<V, L>
+where
+    V: crate::BearerValidator,
+    V::Principal: HasRoles,
+{
+    fn has_role(&self, role: &str) -> bool {
+        self.0.has_role(role)
+    }
+}
+
+#[cfg(feature = "auth-api-key")]
+impl<V, L> HasClaims for crate::ValidatedApiKey<V, L>
+where
+    V: crate::BearerValidator,
+    V::Principal: HasClaims,
+{
+    fn claim(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.0.claim(name)
+    }
+}
+
+#[cfg(feature = "auth-jwt")]
+impl<C: HasRoles> HasRoles for crate::AuthJwt<C> {
+    fn has_role(&self, role: &str) -> bool {
+        self.0.has_role(role)
+    }
+}
+
+#[cfg(feature = "auth-jwt")]
+impl<C: HasClaims> HasClaims for crate::AuthJwt<C> {
+    fn claim(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.0.claim(name)
+    }
+}
+
+/// Policy which passes if both `a` and `b` do, see [all]
+#[derive(Debug, Clone)]
+pub struct All<A, B>(A, B);
+
+/// Creates a policy which passes if both `a` and `b` do, checking `b` only once `a` has passed
+///
+/// Nest these to require more than two policies.
+pub fn all<A, B>(a: A, b: B) -> All<A, B> {
+    All(a, b)
+}
+
+#[async_trait]
+impl<P, A, B> Policy<P> for All<A, B>
+where
+    P: Sync + ?Sized,
+    A: Policy<P>,
+    B: Policy<P>,
+{
+    async fn check(&self, principal: &P, parts: &mut Parts) -> Result<(), AuthError> {
+        self.0.check(principal, parts).await?;
+        self.1.check(principal, parts).await
+    }
+}
+
+/// Policy which passes if either `a` or `b` does, see [any]
+#[derive(Debug, Clone)]
+pub struct Any<A, B>(A, B);
+
+/// Creates a policy which passes if either `a` or `b` does, checking `b` only if `a` didn't pass
+///
+/// Nest these to allow more than two policies. If neither passes, the reasons of both are given.
+pub fn any<A, B>(a: A, b: B) -> Any<A, B> {
+    Any(a, b)
+}
+
+#[async_trait]
+impl<P, A, B> Policy<P> for Any<A, B>
+where
+    P: Sync + ?Sized,
+    A: Policy<P>,
+    B: Policy<P>,
+{
+    async fn check(&self, principal: &P, parts: &mut Parts) -> Result<(), AuthError> {
+        let first = match self.0.check(principal, parts).await {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        };
+        match (first, self.1.check(principal, parts).await) {
+            (_, Ok(())) => Ok(()),
+            (AuthError::Forbidden { reason: a }, Err(AuthError::Forbidden { reason: b })) => {
+                Err(AuthError::forbidden(format!("{}, or {}", a, b)))
+            }
+            // Keep errors which aren't denials like unavailable databases, so they aren't hidden
+            (AuthError::Forbidden { .. }, Err(err)) | (err, Err(_)) => Err(err),
+        }
+    }
+}
+
+/// Policy which passes if `inner` doesn't, see [not]
+#[derive(Debug, Clone)]
+pub struct Not<A>(A);
+
+/// Creates a policy which passes if `inner` is denied
+///
+/// Errors other than [AuthError::Forbidden], such as [AuthError::Unavailable], are passed along instead of being turned into a pass.
+pub fn not<A>(inner: A) -> Not<A> {
+    Not(inner)
+}
+
+#[async_trait]
+impl<P, A> Policy<P> for Not<A>
+where
+    P: Sync + ?Sized,
+    A: Policy<P>,
+{
+    async fn check(&self, principal: &P, parts: &mut Parts) -> Result<(), AuthError> {
+        match self.0.check(principal, parts).await {
+            Ok(()) => Err(AuthError::forbidden("denied by policy")),
+            Err(AuthError::Forbidden { .. }) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Policy which passes if the principal has a role, see [has_role]
+#[derive(Debug, Clone)]
+pub struct HasRole(Cow<'static, str>);
+
+/// Creates a policy which passes if the principal has been given `role`, which it must implement [HasRoles] for
+pub fn has_role(role: impl Into<Cow<'static, str>>) -> HasRole {
+    HasRole(role.into())
+}
+
+#[async_trait]
+impl<P: HasRoles + Sync + ?Sized> Policy<P> for HasRole {
+    async fn check(&self, principal: &P, _parts: &mut Parts) -> Result<(), AuthError> {
+        match principal.has_role(&self.0) {
+            true => Ok(()),
+            false => Err(AuthError::forbidden(format!(
+                "requires the `{}` role",
+                self.0
+            ))),
+        }
+    }
+}
+
+/// Policy which passes if the principal has a claim set to a value, see [has_claim]
+#[derive(Debug, Clone)]
+pub struct HasClaim(Cow<'static, str>, Cow<'static, str>);
+
+/// Creates a policy which passes if the principal's claim called `name` is exactly `value`, which it must implement [HasClaims] for
+pub fn has_claim(
+    name: impl Into<Cow<'static, str>>,
+    value: impl Into<Cow<'static, str>>,
+) -> HasClaim {
+    HasClaim(name.into(), value.into())
+}
+
+#[async_trait]
+impl<P: HasClaims + Sync + ?Sized> Policy<P> for HasClaim {
+    async fn check(&self, principal: &P, _parts: &mut Parts) -> Result<(), AuthError> {
+        match principal.claim(&self.0).as_deref() == Some(&*self.1) {
+            true => Ok(()),
+            false => Err(AuthError::forbidden(format!(
+                "requires `{}` to be `{}`",
+                self.0, self.1
+            ))),
+        }
+    }
+}
+
+/// Extractor which only lets principals of another extractor through if they pass the policy `P`
+///
+/// The wrapped extractor `E` authenticates the request as usual, and once it has, the policy is checked against it and the request parts. The policy is taken out of your router's state using [FromRef], so it should be cheap to clone. The second field only marks which policy was used, so it can be ignored when destructuring.
+///
+/// To guard a whole router instead, use this as the extractor of a [RequireAuthLayer](crate::RequireAuthLayer).
+///
+/// # Example
+///
+/// ```
+/// use async_trait::async_trait;
+/// use axum::{
+///     body::{Body, HttpBody},
+///     extract::FromRef,
+///     http::{request::Parts, Request, StatusCode},
+///     routing::delete,
+///     Router,
+/// };
+/// use axum_auth::{
+///     any, has_role, Any, AuthError, Authorized, BearerValidator, HasRole, HasRoles,
+///     ValidatedBearer,
+/// };
+/// use tower::ServiceExt;
+///
+/// /// User which a token belongs to
+/// struct User {
+///     name: String,
+///     roles: Vec<String>,
+/// }
+///
+/// impl HasRoles for User {
+///     fn has_role(&self, role: &str) -> bool {
+///         self.roles.iter().any(|own| own == role)
+///     }
+/// }
+///
+/// /// Validator which knows about a single user without any roles
+/// #[derive(Clone)]
+/// struct Users;
+///
+/// #[async_trait]
+/// impl BearerValidator for Users {
+///     type Principal = User;
+///
+///     async fn validate(&self, token: &str) -> Result<User, AuthError> {
+///         match token {
+///             "s3cr3t" => Ok(User { name: "alice".to_string(), roles: vec![] }),
+///             _ => Err(AuthError::InvalidToken),
+///         }
+///     }
+/// }
+///
+/// /// Checks if the user is deleting themselves, at `/users/:name`
+/// fn is_self(user: &ValidatedBearer<Users>, parts: &Parts) -> Result<(), AuthError> {
+///     match parts.uri.path().strip_prefix("/users/") == Some(user.0.name.as_str()) {
+///         true => Ok(()),
+///         false => Err(AuthError::forbidden("can only delete yourself")),
+///     }
+/// }
+///
+/// /// Policy letting admins delete anyone, and users delete themselves
+/// type CanDelete = Any<HasRole, fn(&ValidatedBearer<Users>, &Parts) -> Result<(), AuthError>>;
+///
+/// /// State of our app, holding the validator and the policy
+/// #[derive(Clone)]
+/// struct AppState {
+///     users: Users,
+///     can_delete: CanDelete,
+/// }
+///
+/// impl FromRef<AppState> for Users {
+///     fn from_ref(state: &AppState) -> Self {
+///         state.users.clone()
+///     }
+/// }
+///
+/// impl FromRef<AppState> for CanDelete {
+///     fn from_ref(state: &AppState) -> Self {
+///         state.can_delete.clone()
+///     }
+/// }
+///
+/// /// Handler which only runs if the policy lets the user through
+/// async fn handler(
+///     Authorized(ValidatedBearer(user), _): Authorized<ValidatedBearer<Users>, CanDelete>,
+/// ) -> String {
+///     format!("Deleted by {}", user.name)
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let state = AppState { users: Users, can_delete: any(has_role("admin"), is_self as _) };
+/// let app = Router::new().route("/users/:name", delete(handler)).with_state(state);
+///
+/// let req = Request::delete("/users/bob")
+///     .header("Authorization", "Bearer s3cr3t")
+///     .body(Body::empty())
+///     .unwrap();
+/// let res = app.oneshot(req).await.unwrap();
+/// assert_eq!(res.status(), StatusCode::FORBIDDEN);
+/// let body = res.into_body().data().await.unwrap().unwrap();
+/// assert_eq!(body, "Forbidden: requires the `admin` role, or can only delete yourself");
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// On top of the errors the wrapped extractor gives off, this gives off whatever error the policy denied the request with, usually [AuthError::Forbidden]. This is sent as `403 Forbidden` with the reason as the body and without any challenges, as authenticating again won't help. Other errors keep their configured status, apart from ones which would be `401 Unauthorized` or `407 Proxy Authentication Required`, which also become `403 Forbidden` as those need a challenge.
+pub struct Authorized<E, P>(pub E, pub PhantomData<fn() -> P>);
+
+impl<E: fmt::Debug, P> fmt::Debug for Authorized<E, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Authorized").field(&self.0).finish()
+    }
+}
+
+impl<E: Clone, P> Clone for Authorized<E, P> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+#[async_trait]
+impl<S, E, P> FromRequestParts<S> for Authorized<E, P>
+where
+    S: Send + Sync,
+    E: FromRequestParts<S, Rejection = AuthRejection> + Send + Sync,
+    P: Policy<E> + FromRef<S>,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Authenticate as usual then see if the policy lets them through
+        let auth = E::from_request_parts(parts, state).await?;
+        let err = match P::from_ref(state).check(&auth, parts).await {
+            Ok(()) => return Ok(Self(auth, PhantomData)),
+            Err(err) => err,
+        };
+
+        // There's nothing to challenge for once authenticated, so statuses which need a challenge become 403 Forbidden
+        let status = match AuthConfig::from_parts(parts).status_for(&err) {
+            StatusCode::UNAUTHORIZED | StatusCode::PROXY_AUTHENTICATION_REQUIRED => {
+                StatusCode::FORBIDDEN
+            }
+            status => status,
+        };
+        Err(AuthRejection::new(err).with_status(status))
+    }
+}
@@ -0,0 +1,223 @@
+use crate::AuthError;
+use axum_core::response::{IntoResponse, Response};
+use http::{
+    header::{PROXY_AUTHENTICATE, WWW_AUTHENTICATE},
+    HeaderValue, StatusCode,
+};
+use std::{borrow::Cow, fmt};
+
+/// Authentication challenge sent back to clients inside of a `WWW-Authenticate` header
+///
+/// Challenges are made up of a scheme followed by a list of parameters, for example:
+///
+/// ```
+/// use axum_auth::Challenge;
+///
+/// let challenge = Challenge::new("Bearer")
+///     .param("realm", "example")
+///     .param("error", "invalid_request");
+///
+/// assert_eq!(
+///     challenge.to_string(),
+///     r#"Bearer realm="example", error="invalid_request""#
+/// );
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Challenge {
+    scheme: Cow<'static, str>,
+    params: Vec<(Cow<'static, str>, String, bool)>,
+}
+
+impl Challenge {
+    /// Creates a new challenge for the `scheme` given without any parameters
+    pub fn new(scheme: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            scheme: scheme.into(),
+            params: vec![],
+        }
+    }
+
+    /// Adds a parameter which gets sent as a quoted string, e.g. `realm="example"`
+    pub fn param(mut self, name: impl Into<Cow<'static, str>>, value: impl Into<String>) -> Self {
+        self.params.push((name.into(), value.into(), true));
+        self
+    }
+
+    /// Adds a parameter which gets sent as a bare token, e.g. `stale=true`
+    pub fn token_param(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.params.push((name.into(), value.into(), false));
+        self
+    }
+
+    /// Scheme this challenge is for, e.g. `Basic`
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    /// Gets the value of the first parameter called `name` if present
+    pub fn get_param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value, _)| value.as_str())
+    }
+}
+
+impl fmt::Display for Challenge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.scheme)?;
+        for (ind, (name, value, quoted)) in self.params.iter().enumerate() {
+            f.write_str(if ind == 0 { " " } else { ", " })?;
+            if *quoted {
+                write!(f, "{}=\"", name)?;
+                for c in value.chars() {
+                    if c == '"' || c == '\\' {
+                        f.write_str("\\")?;
+                    }
+                    write!(f, "{}", c)?;
+                }
+                f.write_str("\"")?;
+            } else {
+                write!(f, "{}={}", name, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rejection given off by the extractors in this crate, containing the [AuthError] along with how it should be sent
+///
+/// When turned into a response this gives the status code from the [AuthConfig](crate::AuthConfig) in use, `401 Unauthorized` by default, along with a `WWW-Authenticate` header for every [Challenge] so browsers and other clients know how to authenticate. Rejections from proxy extractors such as [ProxyAuthBasic](crate::ProxyAuthBasic) are sent as `407 Proxy Authentication Required` with `Proxy-Authenticate` headers instead.
+///
+/// # Example
+///
+/// This can be taken in as a result to find out why a request was rejected:
+///
+/// ```no_run
+/// use axum_auth::{AuthBearer, AuthError, AuthRejection};
+///
+/// /// Handler which explains to the client why their token was refused
+/// async fn handler(auth: Result<AuthBearer, AuthRejection>) -> String {
+///     match auth {
+///         Ok(AuthBearer(token)) => format!("Found a bearer token: {}", token.expose_secret()),
+///         Err(rejection) if rejection.error() == &AuthError::MissingHeader => {
+///             "Please log in first".to_string()
+///         }
+///         Err(rejection) => format!("Couldn't authenticate: {}", rejection),
+///     }
+/// }
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AuthRejection {
+    error: AuthError,
+    status: StatusCode,
+    challenges: Vec<Challenge>,
+    proxy: bool,
+}
+
+impl AuthRejection {
+    /// Creates a new rejection for an `error` with the error's default status and no challenges
+    pub fn new(error: AuthError) -> Self {
+        Self {
+            status: error.status(),
+            error,
+            challenges: vec![],
+            proxy: false,
+        }
+    }
+
+    /// Overrides the status code this rejection is sent with
+    pub fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Adds a challenge to be sent alongside this rejection
+    pub fn with_challenge(mut self, challenge: Challenge) -> Self {
+        self.challenges.push(challenge);
+        self
+    }
+
+    /// Makes this rejection come from a proxy, sending challenges in `Proxy-Authenticate` headers instead
+    ///
+    /// A status of `401 Unauthorized` is also changed to `407 Proxy Authentication Required`, whilst any other status is left alone.
+    pub fn for_proxy(mut self) -> Self {
+        if self.status == StatusCode::UNAUTHORIZED {
+            self.status = StatusCode::PROXY_AUTHENTICATION_REQUIRED;
+        }
+        self.proxy = true;
+        self
+    }
+
+    /// Error which caused this rejection
+    pub fn error(&self) -> &AuthError {
+        &self.error
+    }
+
+    /// Status code this rejection will be sent with
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Challenges which will be sent alongside this rejection
+    pub fn challenges(&self) -> &[Challenge] {
+        &self.challenges
+    }
+
+    /// Checks if this rejection comes from a proxy, see [AuthRejection::for_proxy]
+    pub fn is_proxy(&self) -> bool {
+        self.proxy
+    }
+
+    /// Takes the error out of this rejection, dropping the status and challenges
+    pub fn into_error(self) -> AuthError {
+        self.error
+    }
+
+    /// Gets the message sent to clients, which names the header proxies read instead if needed
+    fn message(&self) -> String {
+        let message = self.error.to_string();
+        match self.proxy {
+            true => message.replacen("`Authorization`", "`Proxy-Authorization`", 1),
+            false => message,
+        }
+    }
+}
+
+impl From<AuthError> for AuthRejection {
+    fn from(error: AuthError) -> Self {
+        Self::new(error)
+    }
+}
+
+impl fmt::Display for AuthRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+impl std::error::Error for AuthRejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl IntoResponse for AuthRejection {
+    fn into_response(self) -> Response {
+        let mut res = (self.status, self.message()).into_response();
+        let header = match self.proxy {
+            true => PROXY_AUTHENTICATE,
+            false => WWW_AUTHENTICATE,
+        };
+        for challenge in &self.challenges {
+            if let Ok(value) = HeaderValue::from_str(&challenge.to_string()) {
+                res.headers_mut().append(header.clone(), value);
+            }
+        }
+        res
+    }
+}
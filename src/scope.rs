@@ -0,0 +1,195 @@
+use crate::{AuthConfig, AuthRejection, BearerValidator, ValidatedBearer};
+use async_trait::async_trait;
+use axum_core::extract::FromRequestParts;
+use http::request::Parts;
+use std::{fmt, marker::PhantomData};
+
+/// Authenticated principal which carries OAuth scopes, such as a validated token's claims
+///
+/// This is enabled via the `auth-bearer` feature.
+///
+/// It's implemented for [IntrospectedToken](crate::IntrospectedToken) as well as for extractors whose principal implements it, so [RequireScopes] can wrap them. Implement it for your own principals or JWT claims to use them too, or see [HasRoles](crate::HasRoles) for roles.
+///
+/// # Example
+///
+/// ```
+/// use axum_auth::HasScopes;
+/// use serde::Deserialize;
+///
+/// /// Claims of a JWT with a space-separated `scope` claim as described in RFC 8693
+/// #[derive(Deserialize)]
+/// struct Claims {
+///     sub: String,
+///     scope: String,
+/// }
+///
+/// impl HasScopes for Claims {
+///     fn has_scope(&self, scope: &str) -> bool {
+///         self.scope.split_ascii_whitespace().any(|own| own == scope)
+///     }
+/// }
+/// ```
+pub trait HasScopes {
+    /// Checks if this has been granted `scope`
+    fn has_scope(&self, scope: &str) -> bool;
+}
+
+impl<V> HasScopes for ValidatedBearer<V>
+where
+    V: BearerValidator,
+    V::Principal: HasScopes,
+{
+    fn has_scope(&self, scope: &str) -> bool {
+        self.0.has_scope(scope)
+    }
+}
+
+#[cfg(feature = "auth-api-key")]
+impl<V, L> HasScopes for crate::ValidatedApiKey<V, L>
+where
+    V: BearerValidator,
+    V::Principal: HasScopes,
+{
+    fn has_scope(&self, scope: &str) -> bool {
+        self.0.has_scope(scope)
+    }
+}
+
+#[cfg(feature = "auth-jwt")]
+impl<C: HasScopes> HasScopes for crate::AuthJwt<C> {
+    fn has_scope(&self, scope: &str) -> bool {
+        self.0.has_scope(scope)
+    }
+}
+
+/// Set of scopes which a route requires, used as the second parameter of [RequireScopes]
+///
+/// This is enabled via the `auth-bearer` feature.
+///
+/// # Example
+///
+/// ```
+/// use axum_auth::RequiredScopes;
+///
+/// /// Scopes needed to change a user's repositories
+/// struct WriteRepos;
+///
+/// impl RequiredScopes for WriteRepos {
+///     const SCOPES: &'static [&'static str] = &["repo:read", "repo:write"];
+/// }
+/// ```
+pub trait RequiredScopes {
+    /// Scopes which are all required
+    const SCOPES: &'static [&'static str];
+}
+
+/// Extractor which requires the principal of another extractor to have every scope in `R`
+///
+/// This is enabled via the `auth-bearer` feature.
+///
+/// The wrapped extractor `E` authenticates the request as usual, e.g. a [ValidatedBearer] or an [AuthJwt](crate::AuthJwt) whose principal implements [HasScopes]. Once it has, this checks every scope in [RequiredScopes::SCOPES] against it, giving handlers the wrapped extractor if none are missing. The second field only marks which scopes were required, so it can be ignored when destructuring.
+///
+/// To require scopes for a whole router instead, use this as the extractor of a [RequireAuthLayer](crate::RequireAuthLayer).
+///
+/// # Example
+///
+/// ```
+/// use async_trait::async_trait;
+/// use axum::{body::Body, http::{Request, StatusCode}, routing::post, Router};
+/// use axum_auth::{AuthError, BearerValidator, HasScopes, RequireScopes, RequiredScopes, ValidatedBearer};
+/// use tower::ServiceExt;
+///
+/// /// Service which was given a token along with some scopes
+/// struct Service {
+///     name: String,
+///     scopes: Vec<String>,
+/// }
+///
+/// impl HasScopes for Service {
+///     fn has_scope(&self, scope: &str) -> bool {
+///         self.scopes.iter().any(|own| own == scope)
+///     }
+/// }
+///
+/// /// Validator which knows about a single read-only service
+/// #[derive(Clone)]
+/// struct Services;
+///
+/// #[async_trait]
+/// impl BearerValidator for Services {
+///     type Principal = Service;
+///
+///     async fn validate(&self, token: &str) -> Result<Service, AuthError> {
+///         match token {
+///             "s3cr3t" => Ok(Service { name: "ci".to_string(), scopes: vec!["repo:read".to_string()] }),
+///             _ => Err(AuthError::InvalidToken),
+///         }
+///     }
+/// }
+///
+/// /// Scopes needed to change a repository
+/// struct WriteRepos;
+///
+/// impl RequiredScopes for WriteRepos {
+///     const SCOPES: &'static [&'static str] = &["repo:write"];
+/// }
+///
+/// /// Handler which only runs for services allowed to change repositories
+/// async fn handler(
+///     RequireScopes(ValidatedBearer(service), _): RequireScopes<ValidatedBearer<Services>, WriteRepos>,
+/// ) -> String {
+///     format!("Pushing for {}", service.name)
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let app = Router::new().route("/push", post(handler)).with_state(Services);
+///
+/// let req = Request::post("/push")
+///     .header("Authorization", "Bearer s3cr3t")
+///     .body(Body::empty())
+///     .unwrap();
+/// let res = app.oneshot(req).await.unwrap();
+/// assert_eq!(res.status(), StatusCode::FORBIDDEN);
+/// assert_eq!(
+///     res.headers()["WWW-Authenticate"],
+///     r#"Bearer realm="Restricted", error="insufficient_scope", scope="repo:write""#,
+/// );
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// On top of the errors the wrapped extractor gives off, this gives off [AuthError::InsufficientScope](crate::AuthError::InsufficientScope) if any scopes are missing. This is sent as `403 Forbidden` with a `WWW-Authenticate: Bearer realm="...", error="insufficient_scope", scope="..."` challenge listing every required scope as described in RFC 6750.
+pub struct RequireScopes<E, R>(pub E, pub PhantomData<fn() -> R>);
+
+impl<E: fmt::Debug, R> fmt::Debug for RequireScopes<E, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RequireScopes").field(&self.0).finish()
+    }
+}
+
+impl<E: Clone, R> Clone for RequireScopes<E, R> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+#[async_trait]
+impl<S, E, R> FromRequestParts<S> for RequireScopes<E, R>
+where
+    S: Send + Sync,
+    E: FromRequestParts<S, Rejection = AuthRejection> + HasScopes,
+    R: RequiredScopes,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Authenticate as usual then make sure nothing's missing
+        let auth = E::from_request_parts(parts, state).await?;
+        match R::SCOPES.iter().all(|scope| auth.has_scope(scope)) {
+            true => Ok(Self(auth, PhantomData)),
+            false => Err(AuthConfig::from_parts(parts).reject_scope(R::SCOPES)),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::constant_time_eq;
+use std::fmt;
+use zeroize::Zeroize;
+
+/// Secret value such as a password or token, which is redacted when printed and wiped from memory once dropped
+///
+/// This is enabled via the `auth-basic`, `auth-bearer` or `auth-session` features.
+///
+/// Extractors hand out passwords and tokens wrapped in this, so that logging an extractor with `{:?}` doesn't leak them. Getting at the value takes an explicit call to [Secret::expose_secret], and secrets are compared in [constant time](crate::constant_time_eq).
+///
+/// # Example
+///
+/// ```
+/// use axum_auth::Secret;
+///
+/// let token = Secret::new("s3cr3t".to_string());
+/// assert_eq!(format!("{:?}", token), "[REDACTED]");
+/// assert_eq!(token.to_string(), "[REDACTED]");
+/// assert_eq!(token.expose_secret(), "s3cr3t");
+/// assert_eq!(token, Secret::from("s3cr3t"));
+/// ```
+pub struct Secret<T: Zeroize = String>(T);
+
+impl<T: Zeroize> Secret<T> {
+    /// Wraps a value to keep it secret
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Gets the secret value, which should be done as late as possible and never for logging
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl<T: Zeroize + Clone> Clone for Secret<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Zeroize + AsRef<[u8]>> PartialEq for Secret<T> {
+    fn eq(&self, other: &Self) -> bool {
+        constant_time_eq(&self.0, &other.0)
+    }
+}
+
+impl<T: Zeroize + AsRef<[u8]>> Eq for Secret<T> {}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[REDACTED]")
+    }
+}
+
+impl<T: Zeroize> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[REDACTED]")
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
@@ -0,0 +1,544 @@
+use crate::{
+    header::{cookie, is_tchar},
+    AuthConfig, AuthError, AuthRejection,
+};
+use async_trait::async_trait;
+use axum_core::{
+    extract::{FromRef, FromRequestParts},
+    response::{IntoResponse, IntoResponseParts, Response, ResponseParts},
+};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use hmac::{Hmac, Mac};
+use http::{header::SET_COOKIE, request::Parts, HeaderValue};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::Sha256;
+use std::{
+    borrow::Cow,
+    convert::Infallible,
+    fmt,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Longest cookie which browsers are guaranteed to store, counting both its name and value
+const MAX_COOKIE_LEN: usize = 4096;
+
+/// Length of the random nonce put in front of encrypted cookies
+const NONCE_LEN: usize = 24;
+
+/// Keys and attributes used to issue and read session cookies for [AuthSession]
+///
+/// This is enabled via the `auth-session` feature.
+///
+/// Session data is serialized as JSON along with when it expires, then either signed using HMAC-SHA256 so clients can read but not change it, or encrypted using XChaCha20-Poly1305 so they can do neither. Either way the cookie's name is covered too, so cookies can't be swapped for one another, and it's always sent as `HttpOnly` so scripts can't get at it.
+///
+/// By default cookies are called `session`, last for a day, are sent for every path and are only sent over HTTPS with `SameSite=Lax`. Turn off [secure](SessionCookies::secure) when testing locally over plain HTTP.
+///
+/// # Example
+///
+/// ```
+/// use axum_auth::{SameSite, SessionCookies};
+/// use std::time::Duration;
+///
+/// let key = [7; 32]; // load this from your secrets instead
+/// let cookies = SessionCookies::encrypted(&key)
+///     .name("id")
+///     .max_age(Duration::from_secs(60 * 60))
+///     .same_site(SameSite::Strict);
+/// ```
+#[derive(Clone)]
+pub struct SessionCookies {
+    sealing: Arc<Sealing>,
+    max_age: Duration,
+    attributes: CookieAttributes,
+}
+
+/// Way cookies are protected from tampering
+enum Sealing {
+    Signed(Hmac<Sha256>),
+    Encrypted(XChaCha20Poly1305),
+}
+
+/// Session data as stored in a cookie or a [SessionStore](crate::SessionStore), along with when it expires as a unix timestamp
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Session<T> {
+    pub(crate) exp: u64,
+    pub(crate) data: T,
+}
+
+impl SessionCookies {
+    /// Creates cookies which are signed using HMAC-SHA256 with `key`, so their data is readable by clients but can't be changed
+    ///
+    /// # Panics
+    ///
+    /// This panics if `key` is shorter than 32 bytes, which it should be random bytes at least as long as.
+    pub fn signed(key: &[u8]) -> Self {
+        assert!(key.len() >= 32, "Session keys must be at least 32 bytes");
+        let mac =
+            <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC takes keys of any length");
+        Self::with_sealing(Sealing::Signed(mac))
+    }
+
+    /// Creates cookies which are encrypted using XChaCha20-Poly1305 with `key`, so their data can't be read or changed by clients
+    pub fn encrypted(key: &[u8; 32]) -> Self {
+        Self::with_sealing(Sealing::Encrypted(XChaCha20Poly1305::new(key.into())))
+    }
+
+    fn with_sealing(sealing: Sealing) -> Self {
+        Self {
+            sealing: Arc::new(sealing),
+            max_age: Duration::from_secs(24 * 60 * 60),
+            attributes: CookieAttributes::new("session"),
+        }
+    }
+
+    /// Sets the name of the cookie, which is `session` by default
+    ///
+    /// # Panics
+    ///
+    /// This panics if `name` is empty or isn't a valid token as described in RFC 6265.
+    pub fn name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.attributes.set_name(name.into());
+        self
+    }
+
+    /// Gets the name of the cookie
+    pub fn get_name(&self) -> &str {
+        &self.attributes.name
+    }
+
+    /// Sets how long sessions last once issued, which is a day by default
+    ///
+    /// This is checked when reading cookies as well as sent to browsers, so sessions can't be kept alive by holding onto old cookies.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Gets how long sessions last once issued
+    pub fn get_max_age(&self) -> Duration {
+        self.max_age
+    }
+
+    /// Sets the path which browsers send the cookie for, which is `/` by default
+    ///
+    /// # Panics
+    ///
+    /// This panics if `path` contains anything but visible ASCII or contains a `;`.
+    pub fn path(mut self, path: impl Into<Cow<'static, str>>) -> Self {
+        self.attributes.set_path(path.into());
+        self
+    }
+
+    /// Gets the path which browsers send the cookie for
+    pub fn get_path(&self) -> &str {
+        &self.attributes.path
+    }
+
+    /// Sets the domain which browsers send the cookie for, which is only the current host by default
+    ///
+    /// # Panics
+    ///
+    /// This panics if `domain` contains anything but visible ASCII or contains a `;`.
+    pub fn domain(mut self, domain: impl Into<Cow<'static, str>>) -> Self {
+        self.attributes.set_domain(domain.into());
+        self
+    }
+
+    /// Gets the domain which browsers send the cookie for, if it's been set
+    pub fn get_domain(&self) -> Option<&str> {
+        self.attributes.domain.as_deref()
+    }
+
+    /// Sets if browsers should only send the cookie over HTTPS, which is true by default
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.attributes.secure = secure;
+        self
+    }
+
+    /// Gets if browsers should only send the cookie over HTTPS
+    pub fn get_secure(&self) -> bool {
+        self.attributes.secure
+    }
+
+    /// Sets when browsers send the cookie along with requests from other sites, which is [SameSite::Lax] by default
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.attributes.same_site = same_site;
+        self
+    }
+
+    /// Gets when browsers send the cookie along with requests from other sites
+    pub fn get_same_site(&self) -> SameSite {
+        self.attributes.same_site
+    }
+
+    /// Creates a cookie containing `data` which expires after the [max age](SessionCookies::max_age), for sending to clients once they've logged in
+    ///
+    /// # Errors
+    ///
+    /// This gives off [SessionError::Json] if `data` couldn't be serialized, or [SessionError::TooLarge] if the cookie would be too large for browsers to store.
+    pub fn issue<T: Serialize>(&self, data: &T) -> Result<SessionCookie, SessionError> {
+        // Serialize the data along with when it expires then seal it
+        let exp = unix_now().saturating_add(self.max_age.as_secs());
+        let json = serde_json::to_vec(&Session { exp, data }).map_err(SessionError::Json)?;
+        let value = match &*self.sealing {
+            Sealing::Signed(mac) => {
+                let payload = encode(&json);
+                let signature = encode(&self.mac(mac, &payload).finalize().into_bytes());
+                format!("{}.{}", payload, signature)
+            }
+            Sealing::Encrypted(cipher) => {
+                let mut nonce = [0; NONCE_LEN];
+                getrandom::getrandom(&mut nonce).expect("Couldn't generate random bytes");
+                let aad = self.attributes.name.as_bytes();
+                let mut sealed = cipher
+                    .encrypt(XNonce::from_slice(&nonce), Payload { msg: &json, aad })
+                    .expect("Couldn't encrypt session");
+                sealed.splice(0..0, nonce);
+                encode(&sealed)
+            }
+        };
+
+        // Browsers quietly drop cookies which are too long
+        if self.attributes.name.len() + 1 + value.len() > MAX_COOKIE_LEN {
+            return Err(SessionError::TooLarge);
+        }
+        Ok(self.attributes.cookie(&value, Some(self.max_age.as_secs())))
+    }
+
+    /// Creates a cookie which removes the session from browsers, for sending to clients once they've logged out
+    ///
+    /// Signed and encrypted cookies can't be revoked, so copies of the cookie taken beforehand will still work until they expire. Keep the [max age](SessionCookies::max_age) short or store sessions on the server if that matters.
+    pub fn clear(&self) -> SessionCookie {
+        self.attributes.cookie("", Some(0))
+    }
+
+    /// Reads the data out of a session cookie's value, checking that it hasn't been tampered with or expired
+    ///
+    /// # Errors
+    ///
+    /// This gives off [AuthError::InvalidSession] if the cookie wasn't issued using these keys and this name or its data doesn't fit `T`, and [AuthError::ExpiredSession] if it's expired.
+    pub fn read<T: DeserializeOwned>(&self, value: &str) -> Result<T, AuthError> {
+        // Check the signature or decrypt to get the JSON back
+        let json = match &*self.sealing {
+            Sealing::Signed(mac) => {
+                let (payload, signature) =
+                    value.split_once('.').ok_or(AuthError::InvalidSession)?;
+                let signature = decode(signature)?;
+                self.mac(mac, payload)
+                    .verify_slice(&signature)
+                    .map_err(|_| AuthError::InvalidSession)?;
+                decode(payload)?
+            }
+            Sealing::Encrypted(cipher) => {
+                let sealed = decode(value)?;
+                if sealed.len() < NONCE_LEN {
+                    return Err(AuthError::InvalidSession);
+                }
+                let (nonce, msg) = sealed.split_at(NONCE_LEN);
+                let aad = self.attributes.name.as_bytes();
+                cipher
+                    .decrypt(XNonce::from_slice(nonce), Payload { msg, aad })
+                    .map_err(|_| AuthError::InvalidSession)?
+            }
+        };
+
+        // Only give back the data if it's still in date
+        let session: Session<T> =
+            serde_json::from_slice(&json).map_err(|_| AuthError::InvalidSession)?;
+        match session.exp > unix_now() {
+            true => Ok(session.data),
+            false => Err(AuthError::ExpiredSession),
+        }
+    }
+
+    /// Feeds a payload into the MAC along with this cookie's name, ready for signing or verifying
+    fn mac(&self, mac: &Hmac<Sha256>, payload: &str) -> Hmac<Sha256> {
+        let mut mac = mac.clone();
+        mac.update(self.attributes.name.as_bytes());
+        mac.update(b"=");
+        mac.update(payload.as_bytes());
+        mac
+    }
+}
+
+impl fmt::Debug for SessionCookies {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sealing = match &*self.sealing {
+            Sealing::Signed(_) => "signed",
+            Sealing::Encrypted(_) => "encrypted",
+        };
+        f.debug_struct("SessionCookies")
+            .field("sealing", &sealing)
+            .field("max_age", &self.max_age)
+            .field("attributes", &self.attributes)
+            .finish()
+    }
+}
+
+/// Attributes which session cookies are sent with
+#[derive(Debug, Clone)]
+pub(crate) struct CookieAttributes {
+    pub(crate) name: Cow<'static, str>,
+    pub(crate) path: Cow<'static, str>,
+    pub(crate) domain: Option<Cow<'static, str>>,
+    pub(crate) secure: bool,
+    pub(crate) same_site: SameSite,
+}
+
+impl CookieAttributes {
+    /// Creates the default attributes for a cookie called `name`
+    pub(crate) fn new(name: &'static str) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
+            path: Cow::Borrowed("/"),
+            domain: None,
+            secure: true,
+            same_site: SameSite::default(),
+        }
+    }
+
+    /// Sets the name, panicking if it isn't a valid token
+    pub(crate) fn set_name(&mut self, name: Cow<'static, str>) {
+        assert!(
+            !name.is_empty() && name.bytes().all(is_tchar),
+            "Invalid session cookie name"
+        );
+        self.name = name;
+    }
+
+    /// Sets the path, panicking if it would break up the header
+    pub(crate) fn set_path(&mut self, path: Cow<'static, str>) {
+        assert!(is_attribute(&path), "Invalid session cookie path");
+        self.path = path;
+    }
+
+    /// Sets the domain, panicking if it would break up the header
+    pub(crate) fn set_domain(&mut self, domain: Cow<'static, str>) {
+        assert!(is_attribute(&domain), "Invalid session cookie domain");
+        self.domain = Some(domain);
+    }
+
+    /// Creates a `Set-Cookie` header with these attributes, which browsers keep until they're closed if there's no `max_age`
+    pub(crate) fn cookie(&self, value: &str, max_age: Option<u64>) -> SessionCookie {
+        let mut cookie = format!("{}={}", self.name, value);
+        if let Some(max_age) = max_age {
+            cookie.push_str(&format!("; Max-Age={}", max_age));
+        }
+        cookie.push_str(&format!("; Path={}", self.path));
+        if let Some(domain) = &self.domain {
+            cookie.push_str(&format!("; Domain={}", domain));
+        }
+        cookie.push_str("; HttpOnly");
+        if self.secure {
+            cookie.push_str("; Secure");
+        }
+        cookie.push_str(&format!("; SameSite={}", self.same_site.name()));
+        SessionCookie(HeaderValue::try_from(cookie).expect("Cookie attributes are checked"))
+    }
+}
+
+/// When browsers send a cookie along with requests started by other sites, as set using [SessionCookies::same_site]
+///
+/// This is enabled via the `auth-session` feature.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum SameSite {
+    /// Never sent along with requests from other sites
+    Strict,
+    /// Only sent along with requests from other sites when following links to this one
+    #[default]
+    Lax,
+    /// Always sent, which browsers only allow for [secure](SessionCookies::secure) cookies
+    None,
+}
+
+impl SameSite {
+    /// Name of this as sent in cookies, e.g. `Lax`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Strict => "Strict",
+            Self::Lax => "Lax",
+            Self::None => "None",
+        }
+    }
+}
+
+/// `Set-Cookie` header which issues or clears a session, made using [SessionCookies::issue] or [SessionCookies::clear]
+///
+/// This is enabled via the `auth-session` feature.
+///
+/// Return this from handlers, either by itself or alongside the rest of the response, to send it to the client.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SessionCookie(HeaderValue);
+
+impl SessionCookie {
+    /// Gets the value of the `Set-Cookie` header
+    pub fn header_value(&self) -> &HeaderValue {
+        &self.0
+    }
+}
+
+impl IntoResponseParts for SessionCookie {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        res.headers_mut().append(SET_COOKIE, self.0);
+        Ok(res)
+    }
+}
+
+impl IntoResponse for SessionCookie {
+    fn into_response(self) -> Response {
+        (self, ()).into_response()
+    }
+}
+
+/// Error given off when a session cookie couldn't be issued
+///
+/// This is enabled via the `auth-session` feature.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SessionError {
+    /// The session's data couldn't be serialized
+    Json(serde_json::Error),
+    /// The cookie would be larger than the 4096 bytes browsers are guaranteed to store
+    TooLarge,
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "Couldn't serialize session, {}", err),
+            Self::TooLarge => write!(f, "Session is too large to fit in a cookie"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(err) => Some(err),
+            Self::TooLarge => None,
+        }
+    }
+}
+
+/// Session cookie extractor which gives handlers the typed data inside of it
+///
+/// This is enabled via the `auth-session` feature.
+///
+/// Cookies are issued, read and cleared using [SessionCookies] taken out of your router's state using [FromRef]. The data `T` can be anything which serializes to and from JSON, like a user's identifier along with their roles.
+///
+/// # Example
+///
+/// ```
+/// use axum::{
+///     body::Body,
+///     http::{header::{COOKIE, SET_COOKIE}, Request, StatusCode},
+///     extract::State,
+///     response::IntoResponse,
+///     routing::{get, post},
+///     Router,
+/// };
+/// use axum_auth::{AuthSession, SessionCookies};
+/// use serde::{Deserialize, Serialize};
+/// use tower::ServiceExt;
+///
+/// /// Data kept in the session cookie
+/// #[derive(Serialize, Deserialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// /// Handler which logs users in, checking their password first in a real app
+/// async fn login(State(cookies): State<SessionCookies>) -> impl IntoResponse {
+///     let cookie = cookies.issue(&User { name: "alice".to_string() }).unwrap();
+///     (cookie, "Logged in")
+/// }
+///
+/// /// Handler which greets logged in users
+/// async fn me(AuthSession(user): AuthSession<User>) -> String {
+///     format!("Hello, {}", user.name)
+/// }
+///
+/// /// Handler which logs users out
+/// async fn logout(State(cookies): State<SessionCookies>) -> impl IntoResponse {
+///     cookies.clear()
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let cookies = SessionCookies::signed(b"a 32 byte key which is very secret").secure(false);
+/// let app = Router::new()
+///     .route("/login", post(login))
+///     .route("/me", get(me))
+///     .route("/logout", post(logout))
+///     .with_state(cookies);
+///
+/// let req = Request::post("/login").body(Body::empty()).unwrap();
+/// let res = app.clone().oneshot(req).await.unwrap();
+/// let set_cookie = res.headers()[SET_COOKIE].to_str().unwrap();
+/// let cookie = set_cookie.split(';').next().unwrap();
+///
+/// let req = Request::get("/me").header(COOKIE, cookie).body(Body::empty()).unwrap();
+/// let res = app.clone().oneshot(req).await.unwrap();
+/// assert_eq!(res.status(), StatusCode::OK);
+///
+/// let req = Request::post("/logout").body(Body::empty()).unwrap();
+/// let res = app.oneshot(req).await.unwrap();
+/// assert_eq!(res.headers()[SET_COOKIE], "session=; Max-Age=0; Path=/; HttpOnly; SameSite=Lax");
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// This gives off [AuthError::MissingSession] if there's no session cookie, [AuthError::TooLarge] if it's longer than the token limit set in the [AuthConfig], and the errors from [SessionCookies::read] otherwise. Every error is sent as `401 Unauthorized` with a `WWW-Authenticate: Cookie realm="...", cookie-name="..."` challenge naming the session cookie.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AuthSession<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequestParts<S> for AuthSession<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+    SessionCookies: FromRef<S>,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Find the cookie then read the data out of it
+        let config = AuthConfig::from_parts(parts);
+        let cookies = SessionCookies::from_ref(state);
+        let result = match cookie(&parts.headers, cookies.get_name()) {
+            Some(value) if value.len() > config.get_max_token_len() => Err(AuthError::TooLarge),
+            Some(value) => cookies.read(value).map(Self),
+            None => Err(AuthError::MissingSession),
+        };
+        result.map_err(|err| config.reject_session(err, Some(cookies.get_name()), false))
+    }
+}
+
+/// Checks if a cookie attribute's value can be sent without breaking up the header
+fn is_attribute(value: &str) -> bool {
+    value.bytes().all(|b| b.is_ascii_graphic() && b != b';')
+}
+
+/// Encodes bytes as unpadded URL-safe base64, which can be put in cookies as-is
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+/// Decodes unpadded URL-safe base64 from a cookie
+fn decode(input: &str) -> Result<Vec<u8>, AuthError> {
+    base64::decode_config(input, base64::URL_SAFE_NO_PAD).map_err(|_| AuthError::InvalidSession)
+}
+
+/// Gets the current unix timestamp in seconds
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
@@ -0,0 +1,753 @@
+use crate::{
+    header::{auth_header, cookie},
+    session::{encode, unix_now, CookieAttributes, Session},
+    AuthConfig, AuthError, AuthRejection, SameSite, Secret, SessionCookie,
+};
+use async_trait::async_trait;
+use axum_core::extract::{FromRef, FromRequestParts};
+use http::request::Parts;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt, io,
+    marker::PhantomData,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::io::AsyncWriteExt;
+
+/// Number of sessions after which expired ones start getting swept out on every save
+const SWEEP_THRESHOLD: usize = 1024;
+
+/// Number of locks which a [FileStore] spreads session identifiers over, so changes to the same session never interleave
+const FILE_LOCKS: usize = 64;
+
+/// Storage for server-side sessions, keyed by their opaque identifiers
+///
+/// This is enabled via the `auth-session` feature.
+///
+/// This crate comes with a [MemoryStore] and a [FileStore], which are both fine for running on a single node. Implement it yourself to keep sessions in a database shared between nodes, giving off [AuthError::Unavailable] if the database can't be reached.
+///
+/// # Example
+///
+/// ```
+/// use async_trait::async_trait;
+/// use axum_auth::{AuthError, SessionStore};
+/// use std::time::Duration;
+///
+/// /// Store which forgets every session straight away
+/// struct Forgetful;
+///
+/// #[async_trait]
+/// impl SessionStore<String> for Forgetful {
+///     async fn load(&self, _id: &str) -> Result<Option<String>, AuthError> {
+///         Ok(None)
+///     }
+///
+///     async fn save(&self, _id: &str, _data: &String, _ttl: Duration) -> Result<(), AuthError> {
+///         Ok(())
+///     }
+///
+///     async fn delete(&self, _id: &str) -> Result<(), AuthError> {
+///         Ok(())
+///     }
+///
+///     async fn touch(&self, _id: &str, _ttl: Duration) -> Result<bool, AuthError> {
+///         Ok(false)
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait SessionStore<T: Send + Sync>: Send + Sync {
+    /// Loads the data of the session `id`, giving [None] if there's no such session or it has expired
+    async fn load(&self, id: &str) -> Result<Option<T>, AuthError>;
+
+    /// Saves `data` as the session `id`, replacing any existing data and expiring it after `ttl`
+    async fn save(&self, id: &str, data: &T, ttl: Duration) -> Result<(), AuthError>;
+
+    /// Deletes the session `id`, doing nothing if there's no such session
+    async fn delete(&self, id: &str) -> Result<(), AuthError>;
+
+    /// Pushes back the expiry of the session `id` to `ttl` from now, giving false if there's no such session or it has expired
+    ///
+    /// Rolling sessions are touched on every request, so stores where this is costly can skip pushing the expiry back until less than half of `ttl` is left.
+    async fn touch(&self, id: &str, ttl: Duration) -> Result<bool, AuthError>;
+}
+
+#[async_trait]
+impl<T, St> SessionStore<T> for Arc<St>
+where
+    T: Send + Sync,
+    St: SessionStore<T> + ?Sized,
+{
+    async fn load(&self, id: &str) -> Result<Option<T>, AuthError> {
+        (**self).load(id).await
+    }
+
+    async fn save(&self, id: &str, data: &T, ttl: Duration) -> Result<(), AuthError> {
+        (**self).save(id, data, ttl).await
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), AuthError> {
+        (**self).delete(id).await
+    }
+
+    async fn touch(&self, id: &str, ttl: Duration) -> Result<bool, AuthError> {
+        (**self).touch(id, ttl).await
+    }
+}
+
+/// Session store which keeps sessions in memory, losing them once the server stops
+///
+/// This is enabled via the `auth-session` feature.
+///
+/// Clones share the same sessions. Expired sessions are removed once they're loaded, and swept out on every save once there are more than a thousand or so sessions.
+///
+/// # Example
+///
+/// ```
+/// use axum_auth::{MemoryStore, SessionStore};
+/// use std::time::Duration;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let store = MemoryStore::new();
+/// store.save("abc", &"alice".to_string(), Duration::from_secs(60)).await.unwrap();
+/// assert_eq!(store.load("abc").await.unwrap(), Some("alice".to_string()));
+/// # }
+/// ```
+pub struct MemoryStore<T> {
+    sessions: Arc<Mutex<HashMap<String, (T, Instant)>>>,
+}
+
+impl<T> MemoryStore<T> {
+    /// Creates a new store without any sessions
+    pub fn new() -> Self {
+        Self {
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Gets the number of sessions in the store, including expired ones which haven't been removed yet
+    pub fn len(&self) -> usize {
+        self.sessions.lock().unwrap().len()
+    }
+
+    /// Checks if there aren't any sessions in the store
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Default for MemoryStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for MemoryStore<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sessions: self.sessions.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for MemoryStore<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryStore")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<T: Clone + Send + Sync> SessionStore<T> for MemoryStore<T> {
+    async fn load(&self, id: &str) -> Result<Option<T>, AuthError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        match sessions.get(id) {
+            Some((data, expires)) if *expires > Instant::now() => Ok(Some(data.clone())),
+            Some(_) => {
+                sessions.remove(id);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn save(&self, id: &str, data: &T, ttl: Duration) -> Result<(), AuthError> {
+        // Sweep out expired sessions so the store doesn't grow forever
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().unwrap();
+        if sessions.len() >= SWEEP_THRESHOLD {
+            sessions.retain(|_, (_, expires)| *expires > now);
+        }
+        sessions.insert(id.to_string(), (data.clone(), now + ttl));
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), AuthError> {
+        self.sessions.lock().unwrap().remove(id);
+        Ok(())
+    }
+
+    async fn touch(&self, id: &str, ttl: Duration) -> Result<bool, AuthError> {
+        let now = Instant::now();
+        match self.sessions.lock().unwrap().get_mut(id) {
+            Some((_, expires)) if *expires > now => {
+                *expires = now + ttl;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+/// Session store which keeps each session as a JSON file in a directory, so they survive restarts
+///
+/// This is enabled via the `auth-session` feature.
+///
+/// Files are named after a SHA-256 hash of the session's identifier, so identifiers can't be used to reach outside of the directory and can't be found out by listing it. Writes go to a temporary file first which then replaces the session's file, so sessions are never left half-written, and changes to the same session are made one at a time so a touch can't bring back a deleted session or older data. The directory is created on the first save if it doesn't exist yet, and on unix both it and the session files can only be read by the user running the server.
+///
+/// Expired sessions are removed once they're loaded, but sessions which are never loaded again stay around until [FileStore::sweep] is called, so call it every so often from a background task. Touching a session only rewrites its file once less than half of the ttl is left, so rolling sessions don't cause a write on every request.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum_auth::{FileStore, SessionStore};
+/// use std::time::Duration;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let store = FileStore::new("/var/lib/example/sessions");
+/// store.save("abc", &"alice".to_string(), Duration::from_secs(60)).await.unwrap();
+/// assert_eq!(store.load("abc").await.unwrap(), Some("alice".to_string()));
+///
+/// // Clean up every hour or so
+/// let removed = store.sweep().await.unwrap();
+/// # }
+/// ```
+pub struct FileStore<T> {
+    dir: Arc<PathBuf>,
+    locks: Arc<[tokio::sync::Mutex<()>]>,
+    data: PhantomData<fn() -> T>,
+}
+
+impl<T> FileStore<T> {
+    /// Creates a store which keeps sessions in `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: Arc::new(dir.into()),
+            locks: (0..FILE_LOCKS).map(|_| Default::default()).collect(),
+            data: PhantomData,
+        }
+    }
+
+    /// Removes every expired session from the directory, giving back how many were removed
+    ///
+    /// # Errors
+    ///
+    /// This gives off an error if the directory couldn't be read. Files which can't be read or aren't sessions are skipped over.
+    pub async fn sweep(&self) -> io::Result<usize> {
+        let mut entries = match tokio::fs::read_dir(&*self.dir).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err),
+        };
+        let now = unix_now();
+        let mut removed = 0;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().map_or(true, |ext| ext != "json") {
+                continue;
+            }
+            let expired = match tokio::fs::read(&path).await {
+                Ok(json) => serde_json::from_slice::<Expiry>(&json).is_ok_and(|s| s.exp <= now),
+                Err(_) => false,
+            };
+            if expired && tokio::fs::remove_file(&path).await.is_ok() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Gets the path of the file which the session `id` is kept in
+    fn path(&self, id: &str) -> PathBuf {
+        let hash = Sha256::digest(id.as_bytes());
+        let name: String = hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// Locks the session `id` so it can be read and changed without anything else changing it in between
+    async fn lock(&self, id: &str) -> tokio::sync::MutexGuard<'_, ()> {
+        let hash = Sha256::digest(id.as_bytes());
+        self.locks[hash[0] as usize % FILE_LOCKS].lock().await
+    }
+
+    /// Reads a session file, giving [None] if there isn't one
+    async fn read<D: DeserializeOwned>(&self, id: &str) -> Result<Option<Session<D>>, AuthError> {
+        match tokio::fs::read(self.path(id)).await {
+            Ok(json) => serde_json::from_slice(&json)
+                .map(Some)
+                .map_err(|_| AuthError::Unavailable),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(_) => Err(AuthError::Unavailable),
+        }
+    }
+
+    /// Writes a session file through a temporary file so it's replaced in one go, only letting the current user read it
+    async fn write<D: Serialize>(&self, id: &str, session: &Session<D>) -> Result<(), AuthError> {
+        let json = serde_json::to_vec(session).map_err(|_| AuthError::Unavailable)?;
+        let path = self.path(id);
+        let temp = path.with_extension(format!("tmp-{}", random_id()));
+        let result = async {
+            let mut dir = tokio::fs::DirBuilder::new();
+            dir.recursive(true);
+            #[cfg(unix)]
+            dir.mode(0o700);
+            dir.create(&*self.dir).await?;
+
+            let mut file = tokio::fs::OpenOptions::new();
+            file.write(true).create_new(true);
+            #[cfg(unix)]
+            file.mode(0o600);
+            let mut file = file.open(&temp).await?;
+            file.write_all(&json).await?;
+            file.flush().await?;
+            drop(file);
+            tokio::fs::rename(&temp, &path).await
+        };
+        if result.await.is_err() {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(AuthError::Unavailable);
+        }
+        Ok(())
+    }
+
+    /// Removes a session file, doing nothing if there isn't one
+    async fn remove(&self, id: &str) -> Result<(), AuthError> {
+        match tokio::fs::remove_file(self.path(id)).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(_) => Err(AuthError::Unavailable),
+        }
+    }
+}
+
+impl<T> Clone for FileStore<T> {
+    fn clone(&self) -> Self {
+        Self {
+            dir: self.dir.clone(),
+            locks: self.locks.clone(),
+            data: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for FileStore<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileStore").field("dir", &self.dir).finish()
+    }
+}
+
+/// Expiry of a session file, read without the rest of it when sweeping
+#[derive(Deserialize)]
+struct Expiry {
+    exp: u64,
+}
+
+#[async_trait]
+impl<T> SessionStore<T> for FileStore<T>
+where
+    T: Serialize + DeserializeOwned + Send + Sync,
+{
+    async fn load(&self, id: &str) -> Result<Option<T>, AuthError> {
+        let _lock = self.lock(id).await;
+        match self.read::<T>(id).await? {
+            Some(session) if session.exp > unix_now() => Ok(Some(session.data)),
+            Some(_) => {
+                self.remove(id).await?;
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn save(&self, id: &str, data: &T, ttl: Duration) -> Result<(), AuthError> {
+        let _lock = self.lock(id).await;
+        let exp = unix_now().saturating_add(ttl.as_secs());
+        self.write(id, &Session { exp, data }).await
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), AuthError> {
+        let _lock = self.lock(id).await;
+        self.remove(id).await
+    }
+
+    async fn touch(&self, id: &str, ttl: Duration) -> Result<bool, AuthError> {
+        // Rewrite the expiry without needing to understand the data, holding the lock so a delete or save can't be undone
+        let _lock = self.lock(id).await;
+        let now = unix_now();
+        match self.read::<serde_json::Value>(id).await? {
+            Some(mut session) if session.exp > now => {
+                if (session.exp - now).saturating_mul(2) < ttl.as_secs() {
+                    session.exp = now.saturating_add(ttl.as_secs());
+                    self.write(id, &session).await?;
+                }
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+/// Places in a request which [AuthStoredSession] reads session identifiers from
+///
+/// This is enabled via the `auth-session` feature.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum SessionIdSource {
+    /// Only the session cookie, for browsers
+    #[default]
+    Cookie,
+    /// Only an `Authorization: Bearer` header, for other clients
+    Bearer,
+    /// The session cookie if there is one, otherwise an `Authorization: Bearer` header
+    CookieOrBearer,
+}
+
+/// Store and settings used to create, look up and destroy server-side sessions for [AuthStoredSession]
+///
+/// This is enabled via the `auth-session` feature.
+///
+/// Sessions are given random 256-bit identifiers, which clients send back in a cookie or as a bearer token depending on the [source](StoredSessions::source). Only the identifier is given to clients, so unlike [SessionCookies](crate::SessionCookies), sessions can be changed or revoked at any time.
+///
+/// By default sessions expire after a day without being used, and cookies are called `session_id`, are sent for every path and are only sent over HTTPS with `SameSite=Lax`.
+///
+/// # Example
+///
+/// ```
+/// use axum_auth::{MemoryStore, SessionIdSource, StoredSessions};
+/// use std::time::Duration;
+///
+/// let sessions = StoredSessions::<String>::new(MemoryStore::new())
+///     .ttl(Duration::from_secs(30 * 60))
+///     .source(SessionIdSource::CookieOrBearer);
+/// ```
+pub struct StoredSessions<T> {
+    store: Arc<dyn SessionStore<T>>,
+    ttl: Duration,
+    rolling: bool,
+    source: SessionIdSource,
+    attributes: CookieAttributes,
+}
+
+impl<T: Send + Sync> StoredSessions<T> {
+    /// Creates sessions which are kept in `store`
+    pub fn new(store: impl SessionStore<T> + 'static) -> Self {
+        Self {
+            store: Arc::new(store),
+            ttl: Duration::from_secs(24 * 60 * 60),
+            rolling: true,
+            source: SessionIdSource::default(),
+            attributes: CookieAttributes::new("session_id"),
+        }
+    }
+
+    /// Sets how long sessions last, which is a day by default
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Gets how long sessions last
+    pub fn get_ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Sets if sessions are kept alive by using them, which is true by default
+    ///
+    /// Rolling sessions expire once they haven't been used for the [ttl](StoredSessions::ttl), so their cookies are kept until the browser is closed instead of having a `Max-Age`. Otherwise sessions expire once the ttl has passed since they were created, however much they're used.
+    pub fn rolling(mut self, rolling: bool) -> Self {
+        self.rolling = rolling;
+        self
+    }
+
+    /// Gets if sessions are kept alive by using them
+    pub fn get_rolling(&self) -> bool {
+        self.rolling
+    }
+
+    /// Sets where session identifiers are read from, which is [SessionIdSource::Cookie] by default
+    pub fn source(mut self, source: SessionIdSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Gets where session identifiers are read from
+    pub fn get_source(&self) -> SessionIdSource {
+        self.source
+    }
+
+    /// Sets the name of the cookie, which is `session_id` by default
+    ///
+    /// # Panics
+    ///
+    /// This panics if `name` is empty or isn't a valid token as described in RFC 6265.
+    pub fn name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.attributes.set_name(name.into());
+        self
+    }
+
+    /// Gets the name of the cookie
+    pub fn get_name(&self) -> &str {
+        &self.attributes.name
+    }
+
+    /// Sets the path which browsers send the cookie for, which is `/` by default
+    ///
+    /// # Panics
+    ///
+    /// This panics if `path` contains anything but visible ASCII or contains a `;`.
+    pub fn path(mut self, path: impl Into<Cow<'static, str>>) -> Self {
+        self.attributes.set_path(path.into());
+        self
+    }
+
+    /// Sets the domain which browsers send the cookie for, which is only the current host by default
+    ///
+    /// # Panics
+    ///
+    /// This panics if `domain` contains anything but visible ASCII or contains a `;`.
+    pub fn domain(mut self, domain: impl Into<Cow<'static, str>>) -> Self {
+        self.attributes.set_domain(domain.into());
+        self
+    }
+
+    /// Sets if browsers should only send the cookie over HTTPS, which is true by default
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.attributes.secure = secure;
+        self
+    }
+
+    /// Sets when browsers send the cookie along with requests from other sites, which is [SameSite::Lax] by default
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.attributes.same_site = same_site;
+        self
+    }
+
+    /// Creates a new session containing `data`, for once clients have logged in
+    ///
+    /// This gives back the session's identifier, for sending to clients which use it as a bearer token, along with a cookie containing it for browsers.
+    ///
+    /// # Errors
+    ///
+    /// This gives off whatever error the store gave off when saving the session.
+    pub async fn create(&self, data: &T) -> Result<(Secret, SessionCookie), AuthError> {
+        let id = random_id();
+        self.store.save(&id, data, self.ttl).await?;
+        let cookie = self.cookie(&id);
+        Ok((Secret::new(id), cookie))
+    }
+
+    /// Replaces the data of the session `id`, which also pushes back its expiry
+    ///
+    /// # Errors
+    ///
+    /// This gives off whatever error the store gave off when saving the session.
+    pub async fn update(&self, id: &Secret, data: &T) -> Result<(), AuthError> {
+        self.store.save(id.expose_secret(), data, self.ttl).await
+    }
+
+    /// Destroys the session `id`, for once clients have logged out, giving back a cookie which removes it from browsers
+    ///
+    /// # Errors
+    ///
+    /// This gives off whatever error the store gave off when deleting the session.
+    pub async fn destroy(&self, id: &Secret) -> Result<SessionCookie, AuthError> {
+        self.store.delete(id.expose_secret()).await?;
+        Ok(self.attributes.cookie("", Some(0)))
+    }
+
+    /// Gets the store which sessions are kept in
+    pub fn get_store(&self) -> &dyn SessionStore<T> {
+        &*self.store
+    }
+
+    /// Creates a cookie containing the session `id`
+    fn cookie(&self, id: &str) -> SessionCookie {
+        let max_age = (!self.rolling).then_some(self.ttl.as_secs());
+        self.attributes.cookie(id, max_age)
+    }
+
+    /// Finds the session identifier in a request according to the source
+    fn find_id<'a>(&self, parts: &'a Parts, config: &AuthConfig) -> Result<&'a str, AuthError> {
+        let cookie = match self.source {
+            SessionIdSource::Bearer => None,
+            _ => cookie(&parts.headers, &self.attributes.name),
+        };
+        let id = match (cookie, self.source) {
+            (Some(id), _) => id,
+            (None, SessionIdSource::Cookie) => return Err(AuthError::MissingSession),
+            (None, _) => match auth_header(&parts.headers, config)? {
+                ("Bearer", token) => token.trim(),
+                _ => return Err(AuthError::WrongScheme { expected: "Bearer" }),
+            },
+        };
+        match id.len() > config.get_max_token_len() {
+            true => Err(AuthError::TooLarge),
+            false => Ok(id),
+        }
+    }
+}
+
+impl<T> Clone for StoredSessions<T> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            ttl: self.ttl,
+            rolling: self.rolling,
+            source: self.source,
+            attributes: self.attributes.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for StoredSessions<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoredSessions")
+            .field("ttl", &self.ttl)
+            .field("rolling", &self.rolling)
+            .field("source", &self.source)
+            .field("attributes", &self.attributes)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Extractor which looks up a server-side session using the identifier a client sent, giving handlers its identifier and data
+///
+/// This is enabled via the `auth-session` feature.
+///
+/// Sessions are looked up in the [SessionStore] of [StoredSessions] taken out of your router's state using [FromRef], reading the identifier from wherever its [source](StoredSessions::source) says. Rolling sessions have their expiry pushed back every time they're extracted.
+///
+/// # Example
+///
+/// ```
+/// use axum::{
+///     body::Body,
+///     extract::State,
+///     http::{header::{COOKIE, SET_COOKIE}, Request, StatusCode},
+///     response::IntoResponse,
+///     routing::{get, post},
+///     Router,
+/// };
+/// use axum_auth::{AuthError, AuthStoredSession, MemoryStore, StoredSessions};
+/// use tower::ServiceExt;
+///
+/// /// Handler which logs users in, checking their password first in a real app
+/// async fn login(State(sessions): State<StoredSessions<String>>) -> Result<impl IntoResponse, AuthError> {
+///     let (_id, cookie) = sessions.create(&"alice".to_string()).await?;
+///     Ok((cookie, "Logged in"))
+/// }
+///
+/// /// Handler which greets logged in users
+/// async fn me(AuthStoredSession(_, name): AuthStoredSession<String>) -> String {
+///     format!("Hello, {}", name)
+/// }
+///
+/// /// Handler which logs users out, so their session can't be used again
+/// async fn logout(
+///     State(sessions): State<StoredSessions<String>>,
+///     AuthStoredSession(id, _): AuthStoredSession<String>,
+/// ) -> Result<impl IntoResponse, AuthError> {
+///     sessions.destroy(&id).await
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let sessions = StoredSessions::new(MemoryStore::new()).secure(false);
+/// let app = Router::new()
+///     .route("/login", post(login))
+///     .route("/me", get(me))
+///     .route("/logout", post(logout))
+///     .with_state(sessions);
+///
+/// let req = Request::post("/login").body(Body::empty()).unwrap();
+/// let res = app.clone().oneshot(req).await.unwrap();
+/// let cookie = res.headers()[SET_COOKIE].to_str().unwrap().split(';').next().unwrap().to_string();
+///
+/// let req = Request::post("/logout").header(COOKIE, &cookie).body(Body::empty()).unwrap();
+/// let res = app.clone().oneshot(req).await.unwrap();
+/// assert_eq!(res.status(), StatusCode::OK);
+///
+/// let req = Request::get("/me").header(COOKIE, &cookie).body(Body::empty()).unwrap();
+/// let res = app.oneshot(req).await.unwrap();
+/// assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// This gives off [AuthError::MissingSession] if only cookies are accepted and there's no session cookie. When bearer tokens are accepted and there's no cookie to use instead, this gives off [AuthError::MissingHeader] if there's no `Authorization` header and [AuthError::WrongScheme] if it isn't a bearer token. Past that, this gives off [AuthError::TooLarge] if the identifier is longer than the token limit set in the [AuthConfig], and [AuthError::InvalidSession] if there's no such session or it has expired. Errors given off by the store are passed along. Every error is sent as `401 Unauthorized`, apart from the store's [AuthError::Unavailable] which is `503 Service Unavailable`, with a `WWW-Authenticate: Cookie realm="...", cookie-name="..."` challenge naming the session cookie and/or a `WWW-Authenticate: Bearer realm="..."` one depending on where identifiers are read from.
+pub struct AuthStoredSession<T>(pub Secret, pub T);
+
+impl<T: fmt::Debug> fmt::Debug for AuthStoredSession<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AuthStoredSession")
+            .field(&self.0)
+            .field(&self.1)
+            .finish()
+    }
+}
+
+impl<T: Clone> Clone for AuthStoredSession<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), self.1.clone())
+    }
+}
+
+#[async_trait]
+impl<S, T> FromRequestParts<S> for AuthStoredSession<T>
+where
+    S: Send + Sync,
+    T: Send + Sync,
+    StoredSessions<T>: FromRef<S>,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Find the identifier then look it up, keeping rolling sessions alive
+        let config = AuthConfig::from_parts(parts);
+        let sessions = StoredSessions::<T>::from_ref(state);
+        let result = async {
+            let id = sessions.find_id(parts, &config)?;
+            let data = sessions
+                .store
+                .load(id)
+                .await?
+                .ok_or(AuthError::InvalidSession)?;
+            if sessions.rolling {
+                sessions.store.touch(id, sessions.ttl).await?;
+            }
+            Ok(Self(Secret::new(id.to_string()), data))
+        };
+        result.await.map_err(|err| {
+            let cookie = match sessions.source {
+                SessionIdSource::Bearer => None,
+                _ => Some(sessions.get_name()),
+            };
+            let bearer = sessions.source != SessionIdSource::Cookie;
+            config.reject_session(err, cookie, bearer)
+        })
+    }
+}
+
+/// Generates a random 256-bit session identifier encoded as URL-safe base64
+fn random_id() -> String {
+    let mut bytes = [0; 32];
+    getrandom::getrandom(&mut bytes).expect("Couldn't generate random bytes");
+    encode(&bytes)
+}
@@ -0,0 +1,281 @@
+#[cfg(any(feature = "auth-basic", feature = "auth-bearer"))]
+use crate::AuthError;
+#[cfg(any(feature = "auth-basic", feature = "auth-bearer"))]
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+#[cfg(any(feature = "auth-basic", feature = "auth-bearer"))]
+use std::{fmt, sync::Arc};
+#[cfg(any(feature = "auth-basic", feature = "auth-bearer"))]
+use subtle::Choice;
+use subtle::ConstantTimeEq;
+
+/// Verifier for basic authentication credentials, turning them into an authenticated user
+///
+/// This is used by the [VerifiedBasic](crate::VerifiedBasic) extractor, which takes the verifier out of your router's state so credential checks are written once instead of in every handler.
+///
+/// # Example
+///
+/// ```no_run
+/// use async_trait::async_trait;
+/// use axum_auth::{AuthError, BasicVerifier};
+///
+/// /// Verifier which only lets in a single administrator
+/// #[derive(Clone)]
+/// struct Admin;
+///
+/// #[async_trait]
+/// impl BasicVerifier for Admin {
+///     type User = String;
+///
+///     async fn verify(&self, id: &str, password: Option<&str>) -> Result<String, AuthError> {
+///         // NOTE: compare passwords in constant time outside of examples!
+///         match (id, password) {
+///             ("admin", Some("hunter2")) => Ok(id.to_string()),
+///             _ => Err(AuthError::InvalidCredentials),
+///         }
+///     }
+/// }
+/// ```
+#[cfg(feature = "auth-basic")]
+#[async_trait]
+pub trait BasicVerifier: Send + Sync {
+    /// Authenticated user which is given to handlers once verified
+    type User: Send;
+
+    /// Verifies an identifier and optional password, returning the authenticated user
+    ///
+    /// Failures should typically give [AuthError::InvalidCredentials] so that clients are challenged again.
+    async fn verify(&self, id: &str, password: Option<&str>) -> Result<Self::User, AuthError>;
+}
+
+#[cfg(feature = "auth-basic")]
+#[async_trait]
+impl<V> BasicVerifier for Arc<V>
+where
+    V: BasicVerifier + ?Sized,
+{
+    type User = V::User;
+
+    async fn verify(&self, id: &str, password: Option<&str>) -> Result<Self::User, AuthError> {
+        (**self).verify(id, password).await
+    }
+}
+
+/// Validator for bearer tokens, turning them into a typed principal
+///
+/// This is used by the [ValidatedBearer](crate::ValidatedBearer) extractor, which takes the validator out of your router's state so tokens are validated the same way in every handler.
+///
+/// # Example
+///
+/// ```no_run
+/// use async_trait::async_trait;
+/// use axum_auth::{AuthError, BearerValidator};
+///
+/// /// Principal of a validated service token
+/// struct Service {
+///     name: String,
+/// }
+///
+/// /// Validator which knows about a single service's token
+/// #[derive(Clone)]
+/// struct Services;
+///
+/// #[async_trait]
+/// impl BearerValidator for Services {
+///     type Principal = Service;
+///
+///     async fn validate(&self, token: &str) -> Result<Service, AuthError> {
+///         // NOTE: compare tokens in constant time outside of examples!
+///         match token {
+///             "s3cr3t" => Ok(Service { name: "billing".to_string() }),
+///             _ => Err(AuthError::InvalidToken),
+///         }
+///     }
+/// }
+/// ```
+#[cfg(feature = "auth-bearer")]
+#[async_trait]
+pub trait BearerValidator: Send + Sync {
+    /// Principal which is given to handlers once the token is validated
+    type Principal: Send;
+
+    /// Validates a token, returning the principal it belongs to
+    ///
+    /// Failures should typically give [AuthError::InvalidToken] so that clients are sent an `invalid_token` challenge.
+    async fn validate(&self, token: &str) -> Result<Self::Principal, AuthError>;
+}
+
+#[cfg(feature = "auth-bearer")]
+#[async_trait]
+impl<V> BearerValidator for Arc<V>
+where
+    V: BearerValidator + ?Sized,
+{
+    type Principal = V::Principal;
+
+    async fn validate(&self, token: &str) -> Result<Self::Principal, AuthError> {
+        (**self).validate(token).await
+    }
+}
+
+/// Compares two secrets, such as passwords or tokens, in constant time
+///
+/// Comparing secrets using `==` stops at the first differing byte, so attackers can guess them a byte at a time by timing responses. This hashes both inputs first and then compares the hashes in constant time, so neither where they differ nor how long the secrets are is given away.
+///
+/// # Example
+///
+/// ```
+/// use axum_auth::constant_time_eq;
+///
+/// assert!(constant_time_eq("s3cr3t", "s3cr3t"));
+/// assert!(!constant_time_eq("s3cr3t", "s3cr3"));
+/// ```
+pub fn constant_time_eq(a: impl AsRef<[u8]>, b: impl AsRef<[u8]>) -> bool {
+    digest(a).ct_eq(&digest(b)).into()
+}
+
+/// Hashes a secret so it can be compared in constant time regardless of its length
+fn digest(secret: impl AsRef<[u8]>) -> [u8; 32] {
+    Sha256::digest(secret).into()
+}
+
+/// Verifier for basic authentication which checks credentials against a fixed set of users and passwords
+///
+/// This is enabled via the `auth-basic` feature.
+///
+/// Only hashes of the credentials are kept, and every user is checked on every request using [constant-time comparisons](constant_time_eq), so it takes the same time whether the user is unknown or just the password is wrong. That stops usernames from being enumerated by timing responses. Once verified, this gives the user's identifier.
+///
+/// # Example
+///
+/// ```
+/// use axum_auth::{BasicVerifier, StaticBasicCredentials};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let verifier = StaticBasicCredentials::new()
+///     .user("admin", "hunter2")
+///     .user("ci", "0ae1f3c9");
+///
+/// assert_eq!(verifier.verify("admin", Some("hunter2")).await.unwrap(), "admin");
+/// assert!(verifier.verify("admin", Some("0ae1f3c9")).await.is_err());
+/// assert!(verifier.verify("root", Some("hunter2")).await.is_err());
+/// # }
+/// ```
+#[cfg(feature = "auth-basic")]
+#[derive(Clone, Default)]
+pub struct StaticBasicCredentials {
+    users: Arc<Vec<([u8; 32], [u8; 32])>>,
+}
+
+#[cfg(feature = "auth-basic")]
+impl StaticBasicCredentials {
+    /// Creates a new verifier without any users, see [StaticBasicCredentials::user] for adding some
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a user who is let in with the given identifier and password
+    pub fn user(mut self, id: impl AsRef<[u8]>, password: impl AsRef<[u8]>) -> Self {
+        Arc::make_mut(&mut self.users).push((digest(id), digest(password)));
+        self
+    }
+}
+
+#[cfg(feature = "auth-basic")]
+impl fmt::Debug for StaticBasicCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticBasicCredentials")
+            .field("users", &self.users.len())
+            .finish()
+    }
+}
+
+#[cfg(feature = "auth-basic")]
+#[async_trait]
+impl BasicVerifier for StaticBasicCredentials {
+    type User = String;
+
+    async fn verify(&self, id: &str, password: Option<&str>) -> Result<Self::User, AuthError> {
+        // Check against every user so timing doesn't depend on which one matched
+        let (id_digest, password_digest) = (digest(id), digest(password.unwrap_or_default()));
+        let found = self
+            .users
+            .iter()
+            .fold(Choice::from(0), |found, (user, pass)| {
+                found | (user.ct_eq(&id_digest) & pass.ct_eq(&password_digest))
+            });
+        match password.is_some() && bool::from(found) {
+            true => Ok(id.to_string()),
+            false => Err(AuthError::InvalidCredentials),
+        }
+    }
+}
+
+/// Validator for bearer tokens which checks them against one or more fixed tokens, such as a shared secret for internal services
+///
+/// This is enabled via the `auth-bearer` feature.
+///
+/// Only hashes of the tokens are kept, and every token is checked on every request using [constant-time comparisons](constant_time_eq). More than one token can be accepted at a time so they can be rotated without downtime. There's nothing more to a valid token than being valid, so this gives `()`.
+///
+/// # Example
+///
+/// ```
+/// use axum_auth::{BearerValidator, StaticBearerToken};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let validator = StaticBearerToken::new("n3w-s3cr3t").token("0ld-s3cr3t");
+///
+/// assert!(validator.validate("n3w-s3cr3t").await.is_ok());
+/// assert!(validator.validate("0ld-s3cr3t").await.is_ok());
+/// assert!(validator.validate("s3cr3t").await.is_err());
+/// # }
+/// ```
+#[cfg(feature = "auth-bearer")]
+#[derive(Clone)]
+pub struct StaticBearerToken {
+    tokens: Arc<Vec<[u8; 32]>>,
+}
+
+#[cfg(feature = "auth-bearer")]
+impl StaticBearerToken {
+    /// Creates a new validator which accepts a single `token`
+    pub fn new(token: impl AsRef<[u8]>) -> Self {
+        Self {
+            tokens: Arc::new(vec![digest(token)]),
+        }
+    }
+
+    /// Accepts another `token` on top of the ones already accepted
+    pub fn token(mut self, token: impl AsRef<[u8]>) -> Self {
+        Arc::make_mut(&mut self.tokens).push(digest(token));
+        self
+    }
+}
+
+#[cfg(feature = "auth-bearer")]
+impl fmt::Debug for StaticBearerToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticBearerToken")
+            .field("tokens", &self.tokens.len())
+            .finish()
+    }
+}
+
+#[cfg(feature = "auth-bearer")]
+#[async_trait]
+impl BearerValidator for StaticBearerToken {
+    type Principal = ();
+
+    async fn validate(&self, token: &str) -> Result<Self::Principal, AuthError> {
+        // Check against every token so timing doesn't depend on which one matched
+        let token_digest = digest(token);
+        let found = self.tokens.iter().fold(Choice::from(0), |found, token| {
+            found | token.ct_eq(&token_digest)
+        });
+        match bool::from(found) {
+            true => Ok(()),
+            false => Err(AuthError::InvalidToken),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+#![cfg(all(feature = "auth-basic", feature = "auth-bearer"))]
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header, Request, StatusCode},
+    response::{IntoResponse, Response},
+};
+use axum_auth::{AuthAny, AuthConfig, AuthError};
+
+/// Extracts either kind of credentials from an `Authorization` header if there is one
+async fn extract(authorization: Option<&str>) -> Result<AuthAny, (AuthError, Response)> {
+    let mut req = Request::builder();
+    if let Some(authorization) = authorization {
+        req = req.header(header::AUTHORIZATION, authorization);
+    }
+    let (mut parts, _) = req.body(()).unwrap().into_parts();
+    AuthAny::from_request_parts(&mut parts, &())
+        .await
+        .map_err(|rejection| (rejection.error().clone(), rejection.into_response()))
+}
+
+/// Gets every `WWW-Authenticate` challenge of a response
+fn challenges(res: &Response) -> Vec<String> {
+    res.headers()
+        .get_all(header::WWW_AUTHENTICATE)
+        .iter()
+        .map(|value| value.to_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn either_scheme_is_accepted() {
+    assert_eq!(
+        extract(Some("Bearer s3cr3t")).await.unwrap(),
+        AuthAny::Bearer("s3cr3t".into())
+    );
+    let AuthAny::Basic(id, password) = extract(Some("basic YWRtaW46aHVudGVyMg==")).await.unwrap()
+    else {
+        panic!("Expected basic credentials");
+    };
+    assert_eq!(id, "admin");
+    assert_eq!(password.unwrap().expose_secret(), "hunter2");
+}
+
+#[tokio::test]
+async fn other_schemes_get_both_challenges() {
+    let (error, res) = extract(Some(r#"Digest username="admin""#))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        AuthError::WrongScheme {
+            expected: "Basic or Bearer"
+        }
+    );
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        challenges(&res),
+        [
+            r#"Basic realm="Restricted", charset="UTF-8""#,
+            r#"Bearer realm="Restricted""#
+        ]
+    );
+
+    let (error, res) = extract(None).await.unwrap_err();
+    assert_eq!(error, AuthError::MissingHeader);
+    assert_eq!(challenges(&res).len(), 2);
+}
+
+#[tokio::test]
+async fn errors_only_name_the_scheme_they_came_from() {
+    // Broken bearer tokens get an error code
+    let (error, res) = extract(Some("Bearer ")).await.unwrap_err();
+    assert_eq!(error, AuthError::EmptyToken);
+    assert_eq!(
+        challenges(&res),
+        [
+            r#"Basic realm="Restricted", charset="UTF-8""#,
+            r#"Bearer realm="Restricted", error="invalid_request""#
+        ]
+    );
+
+    // Broken basic credentials leave the bearer challenge alone
+    let (error, res) = extract(Some("Basic !!!")).await.unwrap_err();
+    assert_eq!(error, AuthError::InvalidBase64);
+    assert_eq!(
+        challenges(&res),
+        [
+            r#"Basic realm="Restricted", charset="UTF-8""#,
+            r#"Bearer realm="Restricted""#
+        ]
+    );
+}
+
+#[tokio::test]
+async fn rejections_use_the_config() {
+    let (mut parts, _) = Request::builder()
+        .header(header::AUTHORIZATION, "Digest nonce=\"abc\"")
+        .extension(
+            AuthConfig::new()
+                .realm("api")
+                .status_mapping(|_| StatusCode::FORBIDDEN),
+        )
+        .body(())
+        .unwrap()
+        .into_parts();
+    let res = AuthAny::from_request_parts(&mut parts, &())
+        .await
+        .unwrap_err()
+        .into_response();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+        challenges(&res),
+        [
+            r#"Basic realm="api", charset="UTF-8""#,
+            r#"Bearer realm="api""#
+        ]
+    );
+}
@@ -0,0 +1,290 @@
+#![cfg(feature = "auth-api-key")]
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    extract::{FromRef, FromRequestParts},
+    http::{header, Request, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use axum_auth::{
+    ApiKeyCookie, ApiKeyLocation, ApiKeyQuery, ApiKeySource, AuthApiKey, AuthConfig, AuthError,
+    BearerQueryLayer, BearerValidator, RequireAuthLayer, ValidatedApiKey, XApiKey,
+};
+use std::convert::Infallible;
+use tower::{service_fn, Layer, ServiceExt};
+
+/// Extracts a key from `L` behind a [BearerQueryLayer], so it can be read from the query too
+async fn extract_with<L: ApiKeySource<()>>(
+    config: AuthConfig,
+    req: axum::http::request::Builder,
+) -> Result<String, AuthError> {
+    let service = BearerQueryLayer::new().layer(service_fn(|req: Request<()>| async move {
+        let (mut parts, _) = req.into_parts();
+        let key = AuthApiKey::<L>::from_request_parts(&mut parts, &())
+            .await
+            .map(|AuthApiKey(key, _)| key.expose_secret().to_string())
+            .map_err(|rejection| rejection.into_error());
+        Ok::<_, Infallible>(Response::new(key))
+    }));
+    let req = req.extension(config).body(()).unwrap();
+    service.oneshot(req).await.unwrap().into_body()
+}
+
+/// Extracts a key from `L` with a token limit of 8 bytes
+async fn extract<L: ApiKeySource<()>>(
+    req: axum::http::request::Builder,
+) -> Result<String, AuthError> {
+    extract_with::<L>(AuthConfig::new().max_token_len(8), req).await
+}
+
+#[tokio::test]
+async fn header_keys_are_limited() {
+    let ok = extract::<XApiKey>(Request::builder().header("X-API-Key", "12345678")).await;
+    assert_eq!(ok, Ok("12345678".to_string()));
+    let long = extract::<XApiKey>(Request::builder().header("X-API-Key", "123456789")).await;
+    assert_eq!(long, Err(AuthError::TooLarge));
+
+    // Oversized keys are turned away before duplicates are noticed
+    let req = Request::builder()
+        .header("X-API-Key", "123456789")
+        .header("X-API-Key", "123");
+    assert_eq!(extract::<XApiKey>(req).await, Err(AuthError::TooLarge));
+}
+
+#[tokio::test]
+async fn query_keys_are_limited_as_sent() {
+    let ok = extract::<ApiKeyQuery>(Request::builder().uri("/?api_key=12345678")).await;
+    assert_eq!(ok, Ok("12345678".to_string()));
+    let long = extract::<ApiKeyQuery>(Request::builder().uri("/?api_key=123456789")).await;
+    assert_eq!(long, Err(AuthError::TooLarge));
+
+    // Keys are measured before they're decoded
+    let encoded = extract::<ApiKeyQuery>(Request::builder().uri("/?api_key=%31%32%33")).await;
+    assert_eq!(encoded, Err(AuthError::TooLarge));
+
+    // Other parameters aren't limited
+    let req = Request::builder().uri("/?search=a-very-long-search&api_key=1234");
+    assert_eq!(extract::<ApiKeyQuery>(req).await, Ok("1234".to_string()));
+}
+
+#[tokio::test]
+async fn cookie_keys_are_limited() {
+    let req = Request::builder().header("Cookie", "theme=dark-and-long; api_key=12345678");
+    assert_eq!(
+        extract::<ApiKeyCookie>(req).await,
+        Ok("12345678".to_string())
+    );
+    let req = Request::builder().header("Cookie", "api_key=123456789");
+    assert_eq!(extract::<ApiKeyCookie>(req).await, Err(AuthError::TooLarge));
+}
+
+/// Extracts a key from `L` with the default config
+async fn extract_default<L: ApiKeySource<()>>(
+    req: axum::http::request::Builder,
+) -> Result<String, AuthError> {
+    extract_with::<L>(AuthConfig::new(), req).await
+}
+
+#[tokio::test]
+async fn keys_are_read_from_their_location() {
+    let req = || {
+        Request::builder()
+            .uri("/?api_key=from-query")
+            .header("X-API-Key", "from-header")
+            .header("Cookie", "api_key=from-cookie")
+    };
+    assert_eq!(
+        extract_default::<XApiKey>(req()).await,
+        Ok("from-header".to_string())
+    );
+    assert_eq!(
+        extract_default::<ApiKeyCookie>(req()).await,
+        Ok("from-cookie".to_string())
+    );
+    assert_eq!(
+        extract_default::<ApiKeyQuery>(req()).await,
+        Ok("from-query".to_string())
+    );
+
+    // Keys elsewhere aren't looked at
+    let req = Request::builder().header("Authorization", "Bearer s3cr3t");
+    assert_eq!(
+        extract_default::<XApiKey>(req).await,
+        Err(AuthError::MissingApiKey)
+    );
+}
+
+#[tokio::test]
+async fn malformed_keys_are_rejected() {
+    for req in [
+        Request::builder().header("X-API-Key", ""),
+        Request::builder().header("X-API-Key", "two words"),
+        Request::builder()
+            .header("X-API-Key", "first")
+            .header("X-API-Key", "second"),
+    ] {
+        assert_eq!(
+            extract_default::<XApiKey>(req).await,
+            Err(AuthError::MalformedApiKey)
+        );
+    }
+
+    // Browsers may send the same cookie more than once, so the first is used
+    let req = Request::builder().header("Cookie", "api_key=first; api_key=second");
+    assert_eq!(
+        extract_default::<ApiKeyCookie>(req).await,
+        Ok("first".to_string())
+    );
+}
+
+#[tokio::test]
+async fn locations_can_be_found_directly() {
+    let (parts, _) = Request::builder()
+        .uri("/?key=a&api_key=b&api_key=c")
+        .body(())
+        .unwrap()
+        .into_parts();
+    assert_eq!(
+        ApiKeyLocation::Query("key".into()).find(&parts),
+        Ok("a".to_string())
+    );
+    assert_eq!(
+        ApiKeyLocation::Query("api_key".into()).find(&parts),
+        Err(AuthError::MalformedApiKey)
+    );
+    assert_eq!(
+        ApiKeyLocation::Query("other".into()).find(&parts),
+        Err(AuthError::MissingApiKey)
+    );
+}
+
+#[tokio::test]
+async fn rejections_challenge_for_api_keys() {
+    let (mut parts, _) = Request::builder()
+        .extension(AuthConfig::new().realm("api"))
+        .body(())
+        .unwrap()
+        .into_parts();
+    let rejection = AuthApiKey::<XApiKey>::from_request_parts(&mut parts, &())
+        .await
+        .unwrap_err();
+    let res = rejection.into_response();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"ApiKey realm="api""#
+    );
+}
+
+/// Validator which knows about a single service's key
+struct Services;
+
+#[async_trait]
+impl BearerValidator for Services {
+    type Principal = String;
+
+    async fn validate(&self, key: &str) -> Result<String, AuthError> {
+        match key {
+            "s3cr3t" => Ok("billing".to_string()),
+            _ => Err(AuthError::InvalidCredentials),
+        }
+    }
+}
+
+/// Router state picking where keys are read from at runtime
+#[derive(Clone)]
+struct AppState {
+    location: ApiKeyLocation,
+}
+
+impl FromRef<AppState> for ApiKeyLocation {
+    fn from_ref(state: &AppState) -> Self {
+        state.location.clone()
+    }
+}
+
+impl FromRef<AppState> for std::sync::Arc<Services> {
+    fn from_ref(_: &AppState) -> Self {
+        std::sync::Arc::new(Services)
+    }
+}
+
+#[tokio::test]
+async fn validated_keys_use_runtime_locations() {
+    async fn handler(
+        ValidatedApiKey(service, _): ValidatedApiKey<std::sync::Arc<Services>, ApiKeyLocation>,
+    ) -> String {
+        service
+    }
+    let app = Router::new().route("/", get(handler)).with_state(AppState {
+        location: ApiKeyLocation::Header(header::HeaderName::from_static("x-service-key")),
+    });
+    let send = |key: &'static str| {
+        let req = Request::get("/")
+            .header("X-Service-Key", key)
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(req)
+    };
+
+    assert_eq!(send("s3cr3t").await.unwrap().status(), StatusCode::OK);
+    let res = send("0ld-s3cr3t").await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"ApiKey realm="Restricted""#
+    );
+}
+
+#[tokio::test]
+async fn query_keys_need_a_layer() {
+    let (mut parts, _) = Request::builder()
+        .uri("/?api_key=s3cr3t")
+        .body(())
+        .unwrap()
+        .into_parts();
+    let rejection = AuthApiKey::<ApiKeyQuery>::from_request_parts(&mut parts, &())
+        .await
+        .unwrap_err();
+    assert_eq!(rejection.into_error(), AuthError::MissingApiKey);
+}
+
+#[tokio::test]
+async fn responses_to_query_keys_are_private() {
+    async fn handler() -> ([(header::HeaderName, &'static str); 1], &'static str) {
+        ([(header::CACHE_CONTROL, "public, max-age=600")], "ok")
+    }
+    let app = Router::new()
+        .route("/", get(handler))
+        .route_layer(
+            RequireAuthLayer::<AuthApiKey<ApiKeyLocation>, _>::with_state(AppState {
+                location: ApiKeyLocation::Query("key".into()),
+            }),
+        );
+    let send = |uri: &'static str| {
+        let req = Request::get(uri)
+            .header("X-API-Key", "s3cr3t")
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(req)
+    };
+
+    let res = send("/?key=s3cr3t").await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CACHE_CONTROL], "private, max-age=600");
+
+    // Keys elsewhere don't need to be private
+    let by_header = Router::new()
+        .route("/", get(handler))
+        .route_layer(RequireAuthLayer::<AuthApiKey>::new());
+    let req = Request::get("/")
+        .header("X-API-Key", "s3cr3t")
+        .body(Body::empty())
+        .unwrap();
+    let res = by_header.oneshot(req).await.unwrap();
+    assert_eq!(res.headers()[header::CACHE_CONTROL], "public, max-age=600");
+    assert_eq!(send("/").await.unwrap().status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "auth-basic")]
+
+use axum::{
+    extract::FromRequestParts,
+    http::{request::Parts, Request},
+    response::IntoResponse,
+};
+use axum_auth::{AuthBasic, AuthBasicRaw, AuthConfig, AuthError, BasicCharset};
+
+/// ISO-8859-1 encoded `Jürgen:hunter2`, which isn't valid UTF-8
+const LATIN1: &str = "Basic SvxyZ2VuOmh1bnRlcjI=";
+
+/// UTF-8 encoded `Jürgen:hunter2`
+const UTF8: &str = "Basic SsO8cmdlbjpodW50ZXIy";
+
+fn parts(authorization: &str, charset: BasicCharset) -> Parts {
+    Request::builder()
+        .header("Authorization", authorization)
+        .extension(AuthConfig::new().basic_charset(charset))
+        .body(())
+        .unwrap()
+        .into_parts()
+        .0
+}
+
+async fn basic(authorization: &str, charset: BasicCharset) -> Result<String, AuthError> {
+    AuthBasic::from_request_parts(&mut parts(authorization, charset), &())
+        .await
+        .map(|AuthBasic((id, password))| {
+            let password = password.unwrap();
+            format!("{}:{}", id, password.expose_secret())
+        })
+        .map_err(|rejection| rejection.into_error())
+}
+
+#[tokio::test]
+async fn latin1_is_rejected_as_utf8() {
+    let mut parts = parts(LATIN1, BasicCharset::Utf8);
+    let rejection = AuthBasic::from_request_parts(&mut parts, &())
+        .await
+        .unwrap_err();
+    assert_eq!(rejection.error(), &AuthError::InvalidUtf8);
+
+    // Clients are still asked for UTF-8
+    let res = rejection.into_response();
+    assert_eq!(
+        res.headers()["WWW-Authenticate"],
+        r#"Basic realm="Restricted", charset="UTF-8""#
+    );
+}
+
+#[tokio::test]
+async fn latin1_is_decoded_with_a_fallback() {
+    assert_eq!(
+        basic(LATIN1, BasicCharset::Latin1Fallback).await,
+        Ok("Jürgen:hunter2".to_string())
+    );
+}
+
+#[tokio::test]
+async fn utf8_is_always_decoded_as_utf8() {
+    for charset in [BasicCharset::Utf8, BasicCharset::Latin1Fallback] {
+        assert_eq!(
+            basic(UTF8, charset).await,
+            Ok("Jürgen:hunter2".to_string()),
+            "{:?}",
+            charset
+        );
+    }
+}
+
+#[tokio::test]
+async fn raw_credentials_are_given_back_unchanged() {
+    for charset in [BasicCharset::Utf8, BasicCharset::Latin1Fallback] {
+        let mut parts = parts(LATIN1, charset);
+        let AuthBasicRaw((id, password)) = AuthBasicRaw::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+        assert_eq!(id, b"J\xfcrgen");
+        assert_eq!(password.unwrap().expose_secret(), b"hunter2");
+    }
+
+    // Bytes which aren't valid in any charset, i.e. `\xff\xfe:\x80\x00`
+    let mut parts = parts("Basic //46gAA=", BasicCharset::Utf8);
+    let AuthBasicRaw((id, password)) = AuthBasicRaw::from_request_parts(&mut parts, &())
+        .await
+        .unwrap();
+    assert_eq!(id, b"\xff\xfe");
+    assert_eq!(password.unwrap().expose_secret(), b"\x80\x00");
+}
@@ -0,0 +1,161 @@
+#![cfg(all(feature = "auth-basic", feature = "auth-bearer"))]
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Extension, Router,
+};
+use axum_auth::{AuthAny, AuthBasic, AuthBearer, AuthConfig, BearerQueryLayer, RequireAuthLayer};
+use tower::ServiceExt;
+
+async fn bearer(AuthBearer(token): AuthBearer) -> String {
+    token.expose_secret().to_string()
+}
+
+async fn any(auth: AuthAny) -> String {
+    match auth {
+        AuthAny::Basic(id, _) => format!("basic {}", id),
+        AuthAny::Bearer(token) => format!("bearer {}", token.expose_secret()),
+    }
+}
+
+async fn cached(AuthBearer(_): AuthBearer) -> Response {
+    ([(header::CACHE_CONTROL, "no-store")], "cached").into_response()
+}
+
+async fn public(AuthBearer(_): AuthBearer) -> Response {
+    ([(header::CACHE_CONTROL, "public, max-age=600")], "public").into_response()
+}
+
+/// Routes which read tokens from the query themselves, with [AuthConfig::bearer_query] turned on
+fn routes() -> Router {
+    Router::new()
+        .route("/bearer", get(bearer))
+        .route("/any", get(any))
+        .route("/cached", get(cached))
+        .route("/public", get(public))
+        .layer(Extension(AuthConfig::new().bearer_query(true)))
+}
+
+async fn send(app: &Router, uri: &str, authorization: Option<&str>) -> Response {
+    let mut req = Request::get(uri);
+    if let Some(authorization) = authorization {
+        req = req.header(header::AUTHORIZATION, authorization);
+    }
+    app.clone()
+        .oneshot(req.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+}
+
+async fn body(res: Response) -> String {
+    use axum::body::HttpBody;
+    let bytes = res
+        .into_body()
+        .data()
+        .await
+        .unwrap_or(Ok(Default::default()));
+    String::from_utf8(bytes.unwrap().to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn query_tokens_are_ignored_without_a_layer() {
+    let app = routes();
+    for uri in ["/bearer?access_token=s3cr3t", "/any?access_token=s3cr3t"] {
+        let res = send(&app, uri, None).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+        assert!(!res.headers().contains_key(header::CACHE_CONTROL));
+    }
+}
+
+#[tokio::test]
+async fn query_tokens_are_private_behind_bearer_query_layer() {
+    let app = routes().layer(BearerQueryLayer::new());
+
+    let res = send(&app, "/bearer?access_token=s3cr3t", None).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CACHE_CONTROL], "private");
+    assert_eq!(body(res).await, "s3cr3t");
+
+    let res = send(&app, "/any?access_token=s3cr3t", None).await;
+    assert_eq!(res.headers()[header::CACHE_CONTROL], "private");
+    assert_eq!(body(res).await, "bearer s3cr3t");
+
+    // Responses with their own caching rules keep them, unless they'd let shared caches store it
+    let res = send(&app, "/cached?access_token=s3cr3t", None).await;
+    assert_eq!(res.headers()[header::CACHE_CONTROL], "private, no-store");
+    let res = send(&app, "/public?access_token=s3cr3t", None).await;
+    assert_eq!(res.headers()[header::CACHE_CONTROL], "private, max-age=600");
+
+    // Tokens in the header don't need it
+    let res = send(&app, "/bearer", Some("Bearer s3cr3t")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(!res.headers().contains_key(header::CACHE_CONTROL));
+}
+
+#[tokio::test]
+async fn query_tokens_are_private_behind_require_auth_layer() {
+    let app = routes().route_layer(RequireAuthLayer::<AuthBearer>::new());
+    let app = Router::new()
+        .merge(app)
+        .layer(Extension(AuthConfig::new().bearer_query(true)));
+
+    let res = send(&app, "/bearer?access_token=s3cr3t", None).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CACHE_CONTROL], "private");
+}
+
+#[tokio::test]
+async fn query_tokens_are_private_behind_other_require_auth_layers() {
+    let app = routes().route_layer(RequireAuthLayer::<AuthBasic>::new());
+    let app = Router::new()
+        .merge(app)
+        .layer(Extension(AuthConfig::new().bearer_query(true)));
+
+    // The layer checks basic credentials whilst handlers read the token from the query
+    let basic = Some("Basic YWRtaW46aHVudGVyMg==");
+    for uri in ["/bearer?access_token=s3cr3t", "/public?access_token=s3cr3t"] {
+        let res = send(&app, uri, basic).await;
+        assert_eq!(res.status(), StatusCode::OK, "{}", uri);
+        let cache_control = res.headers()[header::CACHE_CONTROL].to_str().unwrap();
+        assert!(cache_control.starts_with("private"), "{}", cache_control);
+        assert!(!cache_control.contains("public"), "{}", cache_control);
+    }
+    let res = send(&app, "/any?access_token=s3cr3t", basic).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(res.headers()[header::CACHE_CONTROL], "private");
+}
+
+#[tokio::test]
+async fn query_tokens_are_only_sent_once() {
+    let app = routes().layer(BearerQueryLayer::new());
+    for (uri, authorization) in [
+        ("/bearer?access_token=a&access_token=b", None),
+        ("/bearer?access_token=a", Some("Bearer b")),
+        ("/any?access_token=a&access_token=b", None),
+        ("/any?access_token=a", Some("Bearer b")),
+        ("/any?access_token=a", Some("Basic YWRtaW46aHVudGVyMg==")),
+    ] {
+        let res = send(&app, uri, authorization).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+        let challenges: Vec<_> = res
+            .headers()
+            .get_all(header::WWW_AUTHENTICATE)
+            .iter()
+            .map(|value| value.to_str().unwrap().to_string())
+            .collect();
+        assert!(
+            challenges
+                .iter()
+                .any(|challenge| challenge.contains(r#"error="invalid_request""#)),
+            "{:?}",
+            challenges
+        );
+    }
+
+    // Basic credentials still work when there's no query token
+    let res = send(&app, "/any", Some("Basic YWRtaW46aHVudGVyMg==")).await;
+    assert_eq!(body(res).await, "basic admin");
+}
@@ -0,0 +1,158 @@
+#![cfg(all(feature = "auth-basic", feature = "auth-bearer"))]
+
+use axum::{
+    body::{Body, HttpBody},
+    http::{header, Request, StatusCode},
+    response::Response,
+    routing::get,
+    Extension, Router,
+};
+use axum_auth::{AuthBasic, AuthBearer, AuthConfig, AuthError, AuthRejection, Challenge};
+use tower::ServiceExt;
+
+async fn basic(AuthBasic((id, _)): AuthBasic) -> String {
+    id
+}
+
+async fn bearer(AuthBearer(token): AuthBearer) -> String {
+    token.expose_secret().to_string()
+}
+
+/// Sends a request to routes using both extractors, configured with `config` if given
+async fn send(config: Option<AuthConfig>, uri: &str, authorization: Option<&str>) -> Response {
+    let mut app = Router::new()
+        .route("/basic", get(basic))
+        .route("/bearer", get(bearer));
+    if let Some(config) = config {
+        app = app.layer(Extension(config));
+    }
+    let mut req = Request::get(uri);
+    if let Some(authorization) = authorization {
+        req = req.header(header::AUTHORIZATION, authorization);
+    }
+    app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+async fn body(res: Response) -> String {
+    let bytes = res.into_body().data().await.unwrap().unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn rejections_challenge_by_default() {
+    let res = send(None, "/basic", None).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Basic realm="Restricted", charset="UTF-8""#
+    );
+    assert_eq!(body(res).await, AuthError::MissingHeader.to_string());
+
+    let res = send(None, "/bearer", None).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Bearer realm="Restricted""#
+    );
+
+    // Malformed bearer tokens get an error code, whilst other schemes don't
+    let res = send(None, "/bearer", Some("Bearer ")).await;
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Bearer realm="Restricted", error="invalid_request""#
+    );
+    let res = send(None, "/bearer", Some("Basic YWRtaW46aHVudGVyMg==")).await;
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Bearer realm="Restricted""#
+    );
+}
+
+#[tokio::test]
+async fn realms_are_configurable_and_escaped() {
+    let config = AuthConfig::new().realm(r#"the "admin" panel"#);
+    let res = send(Some(config.clone()), "/basic", None).await;
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Basic realm="the \"admin\" panel", charset="UTF-8""#
+    );
+    let res = send(Some(config), "/bearer", None).await;
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Bearer realm="the \"admin\" panel""#
+    );
+}
+
+#[tokio::test]
+async fn statuses_are_configurable() {
+    let config = AuthConfig::new().status_mapping(|err| match err {
+        AuthError::MissingHeader => StatusCode::UNAUTHORIZED,
+        _ => StatusCode::BAD_REQUEST,
+    });
+
+    let res = send(Some(config.clone()), "/basic", None).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // Challenges are still sent with other statuses
+    let res = send(Some(config.clone()), "/basic", Some("Basic !!!")).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Basic realm="Restricted", charset="UTF-8""#
+    );
+    let res = send(Some(config), "/bearer", Some("Bearer ")).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Bearer realm="Restricted", error="invalid_request""#
+    );
+
+    // Successes aren't touched
+    let res = send(None, "/bearer", Some("Bearer s3cr3t")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[test]
+fn errors_have_default_statuses() {
+    for error in [
+        AuthError::MissingHeader,
+        AuthError::InvalidCredentials,
+        AuthError::InvalidToken,
+        AuthError::TooLarge,
+    ] {
+        assert_eq!(error.status(), StatusCode::UNAUTHORIZED, "{:?}", error);
+    }
+    assert_eq!(AuthError::InsufficientScope.status(), StatusCode::FORBIDDEN);
+    assert_eq!(AuthError::forbidden("no").status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+        AuthError::Unavailable.status(),
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+}
+
+#[tokio::test]
+async fn rejections_send_every_challenge() {
+    let rejection = AuthRejection::new(AuthError::InvalidToken)
+        .with_status(StatusCode::BAD_REQUEST)
+        .with_challenge(Challenge::new("Basic").param("realm", "a"))
+        .with_challenge(
+            Challenge::new("Bearer")
+                .param("realm", "a")
+                .token_param("max_age", "60"),
+        );
+    assert_eq!(rejection.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(rejection.challenges()[1].get_param("REALM"), Some("a"));
+
+    let res = axum::response::IntoResponse::into_response(rejection);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let challenges: Vec<_> = res
+        .headers()
+        .get_all(header::WWW_AUTHENTICATE)
+        .iter()
+        .map(|value| value.to_str().unwrap().to_string())
+        .collect();
+    assert_eq!(
+        challenges,
+        [r#"Basic realm="a""#, r#"Bearer realm="a", max_age=60"#]
+    );
+}
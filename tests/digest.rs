@@ -0,0 +1,273 @@
+#![cfg(feature = "auth-digest")]
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    response::Response,
+    routing::get,
+    Extension, Router,
+};
+use axum_auth::{AuthConfig, AuthDigest, AuthDigestBody, DigestAlgorithm, DigestAuth, Qop};
+use std::{collections::HashMap, time::Duration};
+use tower::ServiceExt;
+
+const REALM: &str = "http-auth@example.org";
+const URI: &str = "/dir/index.html";
+
+/// App from the examples in RFC 7616 section 3.9.1, with `auth-int` accepted on POSTs
+fn app(digest: DigestAuth) -> Router {
+    async fn get_handler(AuthDigest(username): AuthDigest) -> String {
+        username
+    }
+    async fn post_handler(AuthDigestBody(username, body): AuthDigestBody) -> String {
+        format!("{}:{}", username, String::from_utf8_lossy(&body))
+    }
+    Router::new()
+        .route(URI, get(get_handler).post(post_handler))
+        .layer(Extension(AuthConfig::new().realm(REALM)))
+        .with_state(digest)
+}
+
+fn digest() -> DigestAuth {
+    let users = HashMap::from([("Mufasa".to_string(), "Circle of Life".to_string())]);
+    DigestAuth::new(users)
+}
+
+async fn send(app: &Router, method: &str, authorization: Option<&str>, body: &str) -> Response {
+    let mut req = Request::builder().method(method).uri(URI);
+    if let Some(authorization) = authorization {
+        req = req.header(header::AUTHORIZATION, authorization);
+    }
+    let req = req.body(Body::from(body.to_string())).unwrap();
+    app.clone().oneshot(req).await.unwrap()
+}
+
+/// Gets the challenge for `algorithm` from a rejection
+fn challenge(res: &Response, algorithm: DigestAlgorithm) -> String {
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let token = format!("algorithm={},", algorithm.name());
+    res.headers()
+        .get_all(header::WWW_AUTHENTICATE)
+        .iter()
+        .map(|value| value.to_str().unwrap().to_string())
+        .find(|value| value.contains(&token))
+        .unwrap()
+}
+
+/// Gets a quoted parameter out of a challenge
+fn param<'a>(challenge: &'a str, name: &str) -> &'a str {
+    let start = challenge.find(&format!("{}=\"", name)).unwrap() + name.len() + 2;
+    let len = challenge[start..].find('"').unwrap();
+    &challenge[start..start + len]
+}
+
+/// Client side of a digest exchange for a single nonce
+struct Client {
+    algorithm: DigestAlgorithm,
+    qop: Qop,
+    nonce: String,
+    cnonce: &'static str,
+    password: &'static str,
+}
+
+impl Client {
+    /// Gets a fresh nonce from the app
+    async fn new(app: &Router, algorithm: DigestAlgorithm, qop: Qop) -> Self {
+        let res = send(app, "GET", None, "").await;
+        Self {
+            algorithm,
+            qop,
+            nonce: param(&challenge(&res, algorithm), "nonce").to_string(),
+            cnonce: "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ",
+            password: "Circle of Life",
+        }
+    }
+
+    /// Creates an `Authorization` header for a request
+    fn authorization(&self, method: &str, nc: u32, body: &str) -> String {
+        let alg = self.algorithm;
+        let mut ha1 = alg.hash(format!("Mufasa:{}:{}", REALM, self.password).as_bytes());
+        if alg.is_sess() {
+            ha1 = alg.hash(format!("{}:{}:{}", ha1, self.nonce, self.cnonce).as_bytes());
+        }
+        let ha2 = match self.qop {
+            Qop::Auth => alg.hash(format!("{}:{}", method, URI).as_bytes()),
+            Qop::AuthInt => {
+                alg.hash(format!("{}:{}:{}", method, URI, alg.hash(body.as_bytes())).as_bytes())
+            }
+        };
+        let response = alg.hash(
+            format!(
+                "{}:{}:{:08x}:{}:{}:{}",
+                ha1,
+                self.nonce,
+                nc,
+                self.cnonce,
+                self.qop.name(),
+                ha2
+            )
+            .as_bytes(),
+        );
+        format!(
+            r#"Digest username="Mufasa", realm="{}", uri="{}", algorithm={}, nonce="{}", nc={:08x}, cnonce="{}", qop={}, response="{}""#,
+            REALM,
+            URI,
+            alg.name(),
+            self.nonce,
+            nc,
+            self.cnonce,
+            self.qop.name(),
+            response
+        )
+    }
+}
+
+/// Changes the last character of some hex
+fn tamper(hex: &str) -> String {
+    let (rest, last) = hex.split_at(hex.len() - 1);
+    format!("{}{}", rest, if last == "0" { "1" } else { "0" })
+}
+
+/// Checks a response from RFC 7616 section 3.9.1, whose nonce can't be one of ours and whose opaque is left out
+async fn check_rfc_example(algorithm: DigestAlgorithm, response: &str) {
+    let app = app(digest().algorithms(&[algorithm]));
+    let authorization = |response: &str| {
+        format!(
+            r#"Digest username="Mufasa", realm="{}", uri="{}", algorithm={}, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", nc=00000001, cnonce="f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ", qop=auth, response="{}""#,
+            REALM,
+            URI,
+            algorithm.name(),
+            response
+        )
+    };
+
+    // The right response is only turned away because the nonce is stale, so the client can retry without asking the user
+    let res = send(&app, "GET", Some(&authorization(response)), "").await;
+    assert!(challenge(&res, algorithm).contains("stale=true"));
+
+    // Whereas a wrong one isn't stale
+    let res = send(&app, "GET", Some(&authorization(&tamper(response))), "").await;
+    assert!(!challenge(&res, algorithm).contains("stale"));
+}
+
+#[tokio::test]
+async fn rfc_md5_example() {
+    check_rfc_example(DigestAlgorithm::Md5, "8ca523f5e9506fed4657c9700eebdbec").await;
+}
+
+#[tokio::test]
+async fn rfc_sha256_example() {
+    check_rfc_example(
+        DigestAlgorithm::Sha256,
+        "753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn every_algorithm_authenticates() {
+    let algorithms = [
+        DigestAlgorithm::Md5,
+        DigestAlgorithm::Md5Sess,
+        DigestAlgorithm::Sha256,
+        DigestAlgorithm::Sha256Sess,
+        DigestAlgorithm::Sha512_256,
+        DigestAlgorithm::Sha512_256Sess,
+    ];
+    let app = app(digest().algorithms(&algorithms));
+    for algorithm in algorithms {
+        let client = Client::new(&app, algorithm, Qop::Auth).await;
+        let res = send(&app, "GET", Some(&client.authorization("GET", 1, "")), "").await;
+        assert_eq!(res.status(), StatusCode::OK, "{}", algorithm);
+    }
+}
+
+#[tokio::test]
+async fn wrong_passwords_are_rejected() {
+    let app = app(digest());
+    let mut client = Client::new(&app, DigestAlgorithm::Sha256, Qop::Auth).await;
+    client.password = "Circle of Strife";
+    let res = send(&app, "GET", Some(&client.authorization("GET", 1, "")), "").await;
+    assert!(!challenge(&res, DigestAlgorithm::Sha256).contains("stale"));
+}
+
+#[tokio::test]
+async fn auth_int_protects_the_body() {
+    let app = app(digest().qop(&[Qop::AuthInt, Qop::Auth]));
+    let client = Client::new(&app, DigestAlgorithm::Sha256, Qop::AuthInt).await;
+
+    let authorization = client.authorization("POST", 1, "hello");
+    let res = send(&app, "POST", Some(&authorization), "hello").await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Bodies which have been tampered with don't match
+    let authorization = client.authorization("POST", 2, "hello");
+    let res = send(&app, "POST", Some(&authorization), "goodbye").await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // And auth-int can't be used without reading the body
+    let authorization = client.authorization("GET", 3, "");
+    let res = send(&app, "GET", Some(&authorization), "").await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn replayed_counts_are_stale() {
+    let app = app(digest());
+    let client = Client::new(&app, DigestAlgorithm::Sha256, Qop::Auth).await;
+
+    for nc in [1, 2, 5] {
+        let res = send(&app, "GET", Some(&client.authorization("GET", nc, "")), "").await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+    for nc in [5, 4, 0] {
+        let res = send(&app, "GET", Some(&client.authorization("GET", nc, "")), "").await;
+        assert!(challenge(&res, DigestAlgorithm::Sha256).contains("stale=true"));
+    }
+}
+
+#[tokio::test]
+async fn expired_nonces_are_stale() {
+    let app = app(digest().nonce_lifetime(Duration::from_millis(20)));
+    let client = Client::new(&app, DigestAlgorithm::Sha256, Qop::Auth).await;
+    std::thread::sleep(Duration::from_millis(30));
+
+    let res = send(&app, "GET", Some(&client.authorization("GET", 1, "")), "").await;
+    let challenge = challenge(&res, DigestAlgorithm::Sha256);
+    assert!(challenge.contains("stale=true"));
+
+    // The challenge has a fresh nonce which works
+    let client = Client {
+        nonce: param(&challenge, "nonce").to_string(),
+        ..client
+    };
+    let res = send(&app, "GET", Some(&client.authorization("GET", 1, "")), "").await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn forged_nonces_are_stale() {
+    let app = app(digest());
+    let mut client = Client::new(&app, DigestAlgorithm::Sha256, Qop::Auth).await;
+    client.nonce = tamper(&client.nonce);
+
+    let res = send(&app, "GET", Some(&client.authorization("GET", 1, "")), "").await;
+    assert!(challenge(&res, DigestAlgorithm::Sha256).contains("stale=true"));
+
+    // Nonces from another authenticator aren't accepted either
+    let other_app = self::app(digest());
+    let other = Client::new(&other_app, DigestAlgorithm::Sha256, Qop::Auth).await;
+    let res = send(&app, "GET", Some(&other.authorization("GET", 1, "")), "").await;
+    assert!(challenge(&res, DigestAlgorithm::Sha256).contains("stale=true"));
+}
+
+#[tokio::test]
+async fn unauthenticated_floods_dont_invalidate_nonces() {
+    let app = app(digest());
+    let client = Client::new(&app, DigestAlgorithm::Sha256, Qop::Auth).await;
+    for _ in 0..70_000 {
+        send(&app, "GET", None, "").await;
+    }
+    let res = send(&app, "GET", Some(&client.authorization("GET", 1, "")), "").await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
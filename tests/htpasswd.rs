@@ -0,0 +1,102 @@
+#![cfg(feature = "auth-htpasswd")]
+
+use axum_auth::{BasicVerifier, HtpasswdVerifier};
+
+/// Checks that `hash` accepts `hunter2` but not other passwords, nor the hash itself
+async fn check_format(hash: &str) {
+    let verifier = HtpasswdVerifier::from_contents(&format!("user:{}\n", hash));
+    assert!(
+        verifier.verify("user", Some("hunter2")).await.is_ok(),
+        "{}",
+        hash
+    );
+    assert!(
+        verifier.verify("user", Some("hunter3")).await.is_err(),
+        "{}",
+        hash
+    );
+    assert!(
+        verifier.verify("user", Some(hash)).await.is_err(),
+        "{}",
+        hash
+    );
+    assert!(verifier.verify("user", None).await.is_err(), "{}", hash);
+}
+
+#[tokio::test]
+async fn bcrypt() {
+    check_format("$2y$04$Veu5eHaBvhrNe4blHV5K3OpmC7FcytwTHK70.GfpqUc/bRw7vP5/K").await;
+}
+
+#[tokio::test]
+async fn apr1() {
+    check_format("$apr1$abcdefgh$ckT15POyCRlen.h6XtGAZ1").await;
+}
+
+#[tokio::test]
+async fn md5_crypt() {
+    check_format("$1$abcdefgh$vhxKZ/s1ygZHyCEDPyqtQ/").await;
+}
+
+#[tokio::test]
+async fn sha1() {
+    check_format("{SHA}87u9ZqY9S/F0eUBXjsPQEDUw4h0=").await;
+}
+
+#[tokio::test]
+async fn sha256_crypt() {
+    check_format("$5$abcdefgh$XEPmEiAJvPG31m/DaIsyckkv.Sxd.8NrmElXxCKFQr/").await;
+}
+
+#[tokio::test]
+async fn sha512_crypt() {
+    check_format("$6$abcdefgh$M/eYsB4rVXAm3ZNc88J.UD9rCKAT6FB1rahiwJCHtEndQNORCub5qhjxn50qbqVVthkM.9HpEwtf0t.iV9uH0/").await;
+}
+
+#[tokio::test]
+async fn des_crypt() {
+    check_format("ab0ozUNIgzCZ.").await;
+}
+
+#[tokio::test]
+async fn plaintext() {
+    let verifier = HtpasswdVerifier::from_contents("user:hunter2\n");
+    assert!(verifier.verify("user", Some("hunter2")).await.is_ok());
+    assert!(verifier.verify("user", Some("hunter3")).await.is_err());
+}
+
+#[tokio::test]
+async fn plaintext_passwords_looking_like_des_are_read_as_des() {
+    let verifier = HtpasswdVerifier::from_contents("user:abcdefghijklm\n");
+    assert!(verifier
+        .verify("user", Some("abcdefghijklm"))
+        .await
+        .is_err());
+
+    // Anything outside of DES's alphabet is still plaintext
+    let verifier = HtpasswdVerifier::from_contents("user:abcdefghijkl!\n");
+    assert!(verifier.verify("user", Some("abcdefghijkl!")).await.is_ok());
+}
+
+#[tokio::test]
+async fn unsupported_formats_never_match() {
+    for hash in [
+        "{SSHA}abcd",
+        "$argon2id$v=19$m=65536,t=3,p=4$c2FsdA$aGFzaA",
+        "$y$j9T$salt$hash",
+        "{PBKDF2}hunter2",
+    ] {
+        let verifier = HtpasswdVerifier::from_contents(&format!("user:{}\n", hash));
+        assert!(
+            verifier.verify("user", Some(hash)).await.is_err(),
+            "{}",
+            hash
+        );
+    }
+}
+
+#[tokio::test]
+async fn unknown_users_never_match() {
+    let verifier = HtpasswdVerifier::from_contents("user:hunter2\n");
+    assert!(verifier.verify("root", Some("hunter2")).await.is_err());
+}
@@ -0,0 +1,146 @@
+#![cfg(feature = "auth-introspection")]
+
+use axum::{extract::State, http::HeaderMap, routing::post, Form, Json, Router};
+use axum_auth::{reqwest, AuthError, Introspector};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    net::TcpListener,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Introspection endpoint which knows about a single active token, counting how often it's hit
+async fn introspect(
+    State(hits): State<Arc<AtomicUsize>>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<Json<Value>, axum::http::StatusCode> {
+    hits.fetch_add(1, Ordering::SeqCst);
+    // "my-api:s3cr3t" in base64
+    if headers["authorization"] != "Basic bXktYXBpOnMzY3IzdA==" {
+        return Err(axum::http::StatusCode::UNAUTHORIZED);
+    }
+    Ok(Json(match form["token"].as_str() {
+        "good" => json!({"active": true, "sub": "alice", "scope": "read write", "tenant": "acme"}),
+        "expired" => json!({"active": true, "sub": "bob", "exp": 1}),
+        "broken" => return Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+        _ => json!({"active": false}),
+    }))
+}
+
+/// Serves the mock endpoint on a random local port, giving back its URL and hit counter
+fn serve() -> (String, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/introspect", post(introspect))
+        .with_state(hits.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/introspect", listener.local_addr().unwrap());
+    let server = axum::Server::from_tcp(listener)
+        .unwrap()
+        .serve(app.into_make_service());
+    tokio::spawn(server);
+    (url, hits)
+}
+
+#[tokio::test]
+async fn active_tokens_are_introspected_and_cached() {
+    let (url, hits) = serve();
+    let introspector = Introspector::new(url, "my-api", "s3cr3t");
+
+    let token = introspector.introspect("good").await.unwrap();
+    assert_eq!(token.sub.as_deref(), Some("alice"));
+    assert_eq!(token.scopes().collect::<Vec<_>>(), ["read", "write"]);
+    assert_eq!(token.extra["tenant"], "acme");
+    introspector.introspect("good").await.unwrap();
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn inactive_tokens_are_rejected_and_cached() {
+    let (url, hits) = serve();
+    let introspector = Introspector::new(url, "my-api", "s3cr3t");
+
+    for _ in 0..3 {
+        assert!(matches!(
+            introspector.introspect("unknown").await,
+            Err(AuthError::InvalidToken)
+        ));
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn expired_tokens_are_rejected() {
+    let (url, _) = serve();
+    let introspector = Introspector::new(url, "my-api", "s3cr3t");
+    assert!(matches!(
+        introspector.introspect("expired").await,
+        Err(AuthError::InvalidToken)
+    ));
+}
+
+#[tokio::test]
+async fn endpoint_failures_are_unavailable() {
+    let (url, hits) = serve();
+    let introspector = Introspector::new(url.clone(), "my-api", "s3cr3t");
+    for _ in 0..2 {
+        assert!(matches!(
+            introspector.introspect("broken").await,
+            Err(AuthError::Unavailable)
+        ));
+    }
+    // Failures aren't cached
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    let introspector = Introspector::new(url, "my-api", "wrong");
+    assert!(matches!(
+        introspector.introspect("good").await,
+        Err(AuthError::Unavailable)
+    ));
+}
+
+#[tokio::test]
+async fn unreachable_endpoints_are_unavailable() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/introspect", listener.local_addr().unwrap());
+    drop(listener);
+    let introspector = Introspector::new(url, "my-api", "s3cr3t").client(
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(1))
+            .build()
+            .unwrap(),
+    );
+    assert!(matches!(
+        introspector.introspect("good").await,
+        Err(AuthError::Unavailable)
+    ));
+}
+
+#[tokio::test]
+async fn floods_of_inactive_tokens_dont_push_out_active_ones() {
+    let (url, hits) = serve();
+    let introspector = Introspector::new(url, "my-api", "s3cr3t");
+
+    introspector.introspect("good").await.unwrap();
+    for i in 0..10_000 {
+        assert!(introspector
+            .introspect(&format!("junk-{}", i))
+            .await
+            .is_err());
+    }
+    introspector.introspect("good").await.unwrap();
+    assert_eq!(hits.load(Ordering::SeqCst), 10_001);
+}
+
+#[test]
+fn client_secrets_are_never_shown() {
+    let introspector = Introspector::new("https://auth.example.com/introspect", "my-api", "s3cr3t");
+    let debug = format!("{:?}", introspector);
+    assert!(debug.contains("my-api"), "{}", debug);
+    assert!(!debug.contains("s3cr3t"), "{}", debug);
+}
@@ -0,0 +1,230 @@
+#![cfg(feature = "auth-jwks")]
+
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use axum_auth::{jsonwebtoken::Algorithm, AuthError, JwkKeySet, JwksError};
+use serde_json::{json, Value};
+use std::{
+    net::TcpListener,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// Key set served by the mock endpoint, along with how often it's been fetched
+#[derive(Clone, Default)]
+struct Served {
+    kids: Arc<Mutex<Option<Vec<&'static str>>>>,
+    hits: Arc<AtomicUsize>,
+}
+
+impl Served {
+    /// Changes the kids of the keys served, or makes the endpoint fail if there are none
+    fn set(&self, kids: Option<&[&'static str]>) {
+        *self.kids.lock().unwrap() = kids.map(<[_]>::to_vec);
+    }
+
+    fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+}
+
+async fn jwks(State(served): State<Served>) -> Result<Json<Value>, StatusCode> {
+    served.hits.fetch_add(1, Ordering::SeqCst);
+    let kids = served.kids.lock().unwrap().clone();
+    let keys: Vec<_> = kids
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .map(|kid| json!({"kty": "oct", "kid": kid, "alg": "HS256", "k": "czNjcjN0"}))
+        .collect();
+    Ok(Json(json!({ "keys": keys })))
+}
+
+/// Serves `kids` on a random local port, giving back the endpoint's URL and state
+fn serve(kids: &[&'static str]) -> (String, Served) {
+    let served = Served::default();
+    served.set(Some(kids));
+    let app = Router::new()
+        .route("/jwks.json", get(jwks))
+        .with_state(served.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/jwks.json", listener.local_addr().unwrap());
+    let server = axum::Server::from_tcp(listener)
+        .unwrap()
+        .serve(app.into_make_service());
+    tokio::spawn(server);
+    (url, served)
+}
+
+async fn has(keys: &JwkKeySet, kid: &str) -> Result<(), AuthError> {
+    keys.find(Some(kid), Algorithm::HS256).await.map(|_| ())
+}
+
+#[tokio::test]
+async fn keys_are_fetched_on_first_use_and_cached() {
+    let (url, served) = serve(&["a"]);
+    let keys = JwkKeySet::from_url(url);
+    assert_eq!(served.hits(), 0);
+
+    has(&keys, "a").await.unwrap();
+    has(&keys, "a").await.unwrap();
+    assert!(keys.find(Some("a"), Algorithm::HS512).await.is_err());
+    assert_eq!(served.hits(), 1);
+}
+
+#[tokio::test]
+async fn stale_keys_are_refetched() {
+    let (url, served) = serve(&["a"]);
+    let keys = JwkKeySet::from_url(url)
+        .ttl(Duration::from_millis(50))
+        .cooldown(Duration::ZERO);
+    has(&keys, "a").await.unwrap();
+
+    // Rotated keys are picked up once the old ones are stale
+    served.set(Some(&["b"]));
+    std::thread::sleep(Duration::from_millis(60));
+    has(&keys, "b").await.unwrap();
+    assert_eq!(served.hits(), 2);
+    assert!(has(&keys, "a").await.is_err());
+}
+
+#[tokio::test]
+async fn unknown_kids_cause_a_refetch() {
+    let (url, served) = serve(&["a"]);
+    let keys = JwkKeySet::from_url(url).cooldown(Duration::ZERO);
+    has(&keys, "a").await.unwrap();
+
+    served.set(Some(&["a", "b"]));
+    has(&keys, "b").await.unwrap();
+    assert_eq!(served.hits(), 2);
+    assert!(matches!(
+        has(&keys, "c").await,
+        Err(AuthError::InvalidToken)
+    ));
+    assert_eq!(served.hits(), 3);
+}
+
+#[tokio::test]
+async fn unknown_kids_respect_the_cooldown() {
+    let (url, served) = serve(&["a"]);
+    let keys = JwkKeySet::from_url(url).cooldown(Duration::from_millis(50));
+    has(&keys, "a").await.unwrap();
+
+    // Unknown kids can't hammer the endpoint
+    served.set(Some(&["a", "b"]));
+    for _ in 0..10 {
+        assert!(matches!(
+            has(&keys, "b").await,
+            Err(AuthError::InvalidToken)
+        ));
+    }
+    assert_eq!(served.hits(), 1);
+
+    std::thread::sleep(Duration::from_millis(60));
+    has(&keys, "b").await.unwrap();
+    assert_eq!(served.hits(), 2);
+}
+
+#[tokio::test]
+async fn failed_fetches_are_unavailable_or_keep_old_keys() {
+    let (url, served) = serve(&["a"]);
+    served.set(None);
+    let keys = JwkKeySet::from_url(url)
+        .ttl(Duration::ZERO)
+        .cooldown(Duration::ZERO);
+    assert!(keys.refresh().await.is_err());
+    assert!(matches!(has(&keys, "a").await, Err(AuthError::Unavailable)));
+
+    // Keys that were loaded keep being used if reloading them fails
+    served.set(Some(&["a"]));
+    has(&keys, "a").await.unwrap();
+    served.set(None);
+    has(&keys, "a").await.unwrap();
+}
+
+#[tokio::test]
+async fn unreachable_urls_are_unavailable() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/jwks.json", listener.local_addr().unwrap());
+    drop(listener);
+    let client = axum_auth::reqwest::Client::builder()
+        .timeout(Duration::from_secs(1))
+        .build()
+        .unwrap();
+    let keys = JwkKeySet::from_url_with_client(url, client);
+    assert!(keys.refresh().await.is_err());
+    assert!(matches!(has(&keys, "a").await, Err(AuthError::Unavailable)));
+}
+
+#[tokio::test]
+async fn oversized_key_sets_are_refused() {
+    /// Key set padded out past the limit, which says how long it is up-front
+    async fn huge() -> String {
+        let padding = "x".repeat(300 * 1024);
+        format!(r#"{{"keys": [], "padding": "{}"}}"#, padding)
+    }
+
+    /// Key set which never ends, and never says how long it is
+    async fn endless() -> axum::response::Response {
+        let (mut sender, body) = axum::body::Body::channel();
+        tokio::spawn(async move {
+            let _ = sender
+                .send_data(r#"{"keys": [], "padding": ""#.into())
+                .await;
+            while sender.send_data(vec![b'x'; 1024].into()).await.is_ok() {}
+        });
+        axum::response::Response::new(axum::body::boxed(body))
+    }
+
+    let app = Router::new()
+        .route("/huge.json", get(huge))
+        .route("/endless.json", get(endless));
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = axum::Server::from_tcp(listener)
+        .unwrap()
+        .serve(app.into_make_service());
+    tokio::spawn(server);
+
+    for path in ["huge", "endless"] {
+        let keys = JwkKeySet::from_url(format!("http://{}/{}.json", addr, path));
+        assert!(
+            matches!(keys.refresh().await, Err(JwksError::TooLarge)),
+            "{}",
+            path
+        );
+        assert!(matches!(has(&keys, "a").await, Err(AuthError::Unavailable)));
+    }
+}
+
+#[tokio::test]
+async fn ec_keys_without_an_alg_are_pinned_by_their_curve() {
+    let keys = json!({ "keys": [
+        {
+            "kty": "EC", "kid": "p256", "crv": "P-256",
+            "x": "oassbP53okMyWzbhqth1N0Bc50OHDSnd0fUPc92B2q4",
+            "y": "-FOB9nbBvkgjbPapigHqXN41He8BJBk1YGCJZ_JNp2w",
+        },
+        {
+            "kty": "EC", "kid": "p384", "crv": "P-384",
+            "x": "8pT8FPJEqVBa33BYXXaT0qtjdh-3nOVoEH_rYjg_cbXpljuUzeeEdmZh8yTOyvGR",
+            "y": "VgpiQ7SWaMRCyA5ebClndT8A2u6rFZUydcq9Zp-2gSBTaeII_WP04Q3QxqZNoGzR",
+        },
+        // Claims an algorithm its curve can't be used with
+        {
+            "kty": "EC", "kid": "mismatched", "crv": "P-256", "alg": "ES384",
+            "x": "oassbP53okMyWzbhqth1N0Bc50OHDSnd0fUPc92B2q4",
+            "y": "-FOB9nbBvkgjbPapigHqXN41He8BJBk1YGCJZ_JNp2w",
+        },
+    ]});
+    let keys = JwkKeySet::from_slice(keys.to_string().as_bytes()).unwrap();
+
+    assert!(keys.find(Some("p256"), Algorithm::ES256).await.is_ok());
+    assert!(keys.find(Some("p256"), Algorithm::ES384).await.is_err());
+    assert!(keys.find(Some("p384"), Algorithm::ES384).await.is_ok());
+    assert!(keys.find(Some("p384"), Algorithm::ES256).await.is_err());
+    for alg in [Algorithm::ES256, Algorithm::ES384] {
+        assert!(keys.find(Some("mismatched"), alg).await.is_err());
+    }
+}
@@ -0,0 +1,273 @@
+#![cfg(feature = "auth-jwt")]
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header, Request, StatusCode},
+    response::IntoResponse,
+};
+use axum_auth::{
+    jsonwebtoken::{self, Algorithm, DecodingKey, EncodingKey, Header},
+    AuthJwt, JwtDecoder,
+};
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Gets the private key used to sign tokens with `algorithm`
+fn encoding_key(algorithm: Algorithm) -> EncodingKey {
+    match algorithm {
+        Algorithm::RS256 => EncodingKey::from_rsa_pem(include_bytes!("keys/rsa.pem")),
+        Algorithm::ES256 => EncodingKey::from_ec_pem(include_bytes!("keys/ec.pem")),
+        Algorithm::EdDSA => EncodingKey::from_ed_pem(include_bytes!("keys/ed.pem")),
+        _ => unreachable!(),
+    }
+    .unwrap()
+}
+
+/// Gets the public key used to check tokens signed with `algorithm`
+fn decoding_key(algorithm: Algorithm) -> DecodingKey {
+    match algorithm {
+        Algorithm::RS256 => DecodingKey::from_rsa_pem(public_pem(algorithm)),
+        Algorithm::ES256 => DecodingKey::from_ec_pem(public_pem(algorithm)),
+        Algorithm::EdDSA => DecodingKey::from_ed_pem(public_pem(algorithm)),
+        _ => unreachable!(),
+    }
+    .unwrap()
+}
+
+fn public_pem(algorithm: Algorithm) -> &'static [u8] {
+    match algorithm {
+        Algorithm::RS256 => include_bytes!("keys/rsa.pub.pem"),
+        Algorithm::ES256 => include_bytes!("keys/ec.pub.pem"),
+        Algorithm::EdDSA => include_bytes!("keys/ed.pub.pem"),
+        _ => unreachable!(),
+    }
+}
+
+const ALGORITHMS: [Algorithm; 3] = [Algorithm::RS256, Algorithm::ES256, Algorithm::EdDSA];
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+fn sign(algorithm: Algorithm, claims: &Value) -> String {
+    jsonwebtoken::encode(&Header::new(algorithm), claims, &encoding_key(algorithm)).unwrap()
+}
+
+/// Runs a token through the extractor, giving back its claims or the rejection's status and challenge
+async fn extract(decoder: &JwtDecoder, token: &str) -> Result<Value, (StatusCode, String)> {
+    let (mut parts, _) = Request::builder()
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(())
+        .unwrap()
+        .into_parts();
+    match AuthJwt::<Value>::from_request_parts(&mut parts, decoder).await {
+        Ok(AuthJwt(claims)) => Ok(claims),
+        Err(rejection) => {
+            let res = rejection.into_response();
+            let challenge = res.headers()[header::WWW_AUTHENTICATE].to_str().unwrap();
+            Err((res.status(), challenge.to_string()))
+        }
+    }
+}
+
+async fn accepts(decoder: &JwtDecoder, algorithm: Algorithm, claims: Value) -> bool {
+    let token = sign(algorithm, &claims);
+    match extract(decoder, &token).await {
+        Ok(decoded) => {
+            assert_eq!(decoded, claims);
+            true
+        }
+        Err((status, challenge)) => {
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert!(
+                challenge.contains(r#"error="invalid_token""#),
+                "{}",
+                challenge
+            );
+            false
+        }
+    }
+}
+
+#[tokio::test]
+async fn valid_tokens_are_accepted() {
+    for algorithm in ALGORITHMS {
+        let decoder = JwtDecoder::new(algorithm, decoding_key(algorithm));
+        let claims = json!({"sub": "ferris", "exp": now() + 60});
+        assert!(
+            accepts(&decoder, algorithm, claims).await,
+            "{:?}",
+            algorithm
+        );
+    }
+}
+
+#[tokio::test]
+async fn tampered_signatures_are_rejected() {
+    for algorithm in ALGORITHMS {
+        let decoder = JwtDecoder::new(algorithm, decoding_key(algorithm));
+        let token = sign(algorithm, &json!({"sub": "ferris", "exp": now() + 60}));
+
+        // Swap the payload for another one, keeping the signature
+        let forged = sign(algorithm, &json!({"sub": "admin", "exp": now() + 60}));
+        let (header, rest) = token.split_once('.').unwrap();
+        let (_, signature) = rest.split_once('.').unwrap();
+        let (_, forged_rest) = forged.split_once('.').unwrap();
+        let (forged_payload, _) = forged_rest.split_once('.').unwrap();
+        let swapped = format!("{}.{}.{}", header, forged_payload, signature);
+        assert!(
+            extract(&decoder, &swapped).await.is_err(),
+            "{:?}",
+            algorithm
+        );
+
+        // Flip a bit of the signature itself
+        let mut bytes = token.into_bytes();
+        let last = bytes.len() - 5;
+        bytes[last] = if bytes[last] == b'A' { b'B' } else { b'A' };
+        let tampered = String::from_utf8(bytes).unwrap();
+        assert!(
+            extract(&decoder, &tampered).await.is_err(),
+            "{:?}",
+            algorithm
+        );
+    }
+}
+
+#[tokio::test]
+async fn other_keys_and_algorithms_are_rejected() {
+    let claims = json!({"sub": "ferris", "exp": now() + 60});
+    for algorithm in ALGORITHMS {
+        let decoder = JwtDecoder::new(algorithm, decoding_key(algorithm));
+        for other in ALGORITHMS.into_iter().filter(|other| *other != algorithm) {
+            assert!(!accepts(&decoder, other, claims.clone()).await);
+        }
+
+        // Public keys can't be used as HMAC secrets
+        let token = jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(public_pem(algorithm)),
+        )
+        .unwrap();
+        assert!(extract(&decoder, &token).await.is_err(), "{:?}", algorithm);
+    }
+}
+
+#[tokio::test]
+async fn exp_is_checked_with_leeway() {
+    let decoder = JwtDecoder::new(Algorithm::ES256, decoding_key(Algorithm::ES256));
+    let sign_exp = |exp| json!({"sub": "ferris", "exp": exp});
+
+    // The default leeway is 60 seconds
+    assert!(accepts(&decoder, Algorithm::ES256, sign_exp(now() - 30)).await);
+    assert!(!accepts(&decoder, Algorithm::ES256, sign_exp(now() - 120)).await);
+
+    let decoder = decoder.leeway(0);
+    assert!(!accepts(&decoder, Algorithm::ES256, sign_exp(now() - 30)).await);
+    let decoder = decoder.leeway(300);
+    assert!(accepts(&decoder, Algorithm::ES256, sign_exp(now() - 120)).await);
+
+    // Tokens have to expire
+    assert!(!accepts(&decoder, Algorithm::ES256, json!({"sub": "ferris"})).await);
+}
+
+#[tokio::test]
+async fn nbf_is_checked_with_leeway() {
+    let decoder = JwtDecoder::new(Algorithm::EdDSA, decoding_key(Algorithm::EdDSA));
+    let sign_nbf = |nbf| json!({"sub": "ferris", "exp": now() + 600, "nbf": nbf});
+
+    assert!(accepts(&decoder, Algorithm::EdDSA, sign_nbf(now() - 10)).await);
+    assert!(accepts(&decoder, Algorithm::EdDSA, sign_nbf(now() + 30)).await);
+    assert!(!accepts(&decoder, Algorithm::EdDSA, sign_nbf(now() + 120)).await);
+
+    let decoder = decoder.leeway(0);
+    assert!(!accepts(&decoder, Algorithm::EdDSA, sign_nbf(now() + 30)).await);
+}
+
+#[tokio::test]
+async fn iss_is_checked_once_set() {
+    let decoder = JwtDecoder::new(Algorithm::RS256, decoding_key(Algorithm::RS256));
+    let sign_iss = |iss| json!({"sub": "ferris", "exp": now() + 60, "iss": iss});
+    assert!(
+        accepts(
+            &decoder,
+            Algorithm::RS256,
+            sign_iss("https://evil.example.com")
+        )
+        .await
+    );
+
+    let decoder = decoder.issuer(&["https://auth.example.com"]);
+    assert!(
+        accepts(
+            &decoder,
+            Algorithm::RS256,
+            sign_iss("https://auth.example.com")
+        )
+        .await
+    );
+    assert!(
+        !accepts(
+            &decoder,
+            Algorithm::RS256,
+            sign_iss("https://evil.example.com")
+        )
+        .await
+    );
+    assert!(
+        !accepts(
+            &decoder,
+            Algorithm::RS256,
+            json!({"sub": "ferris", "exp": now() + 60})
+        )
+        .await
+    );
+}
+
+#[tokio::test]
+async fn aud_is_checked_once_set() {
+    let decoder = JwtDecoder::new(Algorithm::RS256, decoding_key(Algorithm::RS256));
+    let sign_aud = |aud: Value| json!({"sub": "ferris", "exp": now() + 60, "aud": aud});
+    assert!(accepts(&decoder, Algorithm::RS256, sign_aud(json!("other-api"))).await);
+
+    let decoder = decoder.audience(&["my-api"]);
+    assert!(accepts(&decoder, Algorithm::RS256, sign_aud(json!("my-api"))).await);
+    assert!(
+        accepts(
+            &decoder,
+            Algorithm::RS256,
+            sign_aud(json!(["other-api", "my-api"]))
+        )
+        .await
+    );
+    assert!(!accepts(&decoder, Algorithm::RS256, sign_aud(json!("other-api"))).await);
+    assert!(!accepts(&decoder, Algorithm::RS256, sign_aud(json!(["other-api"]))).await);
+    assert!(
+        !accepts(
+            &decoder,
+            Algorithm::RS256,
+            json!({"sub": "ferris", "exp": now() + 60})
+        )
+        .await
+    );
+}
+
+#[tokio::test]
+async fn required_claims_must_be_present() {
+    let decoder =
+        JwtDecoder::new(Algorithm::ES256, decoding_key(Algorithm::ES256)).required_claims(&["sub"]);
+    assert!(
+        accepts(
+            &decoder,
+            Algorithm::ES256,
+            json!({"sub": "ferris", "exp": now() + 60})
+        )
+        .await
+    );
+    assert!(!accepts(&decoder, Algorithm::ES256, json!({"exp": now() + 60})).await);
+    assert!(!accepts(&decoder, Algorithm::ES256, json!({"sub": "ferris"})).await);
+}
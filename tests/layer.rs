@@ -0,0 +1,121 @@
+#![cfg(all(feature = "auth-basic", feature = "auth-bearer"))]
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    response::Response,
+    routing::get,
+    Extension, Router,
+};
+use axum_auth::{
+    AuthBasic, AuthBearer, AuthConfig, RequireAuthLayer, StaticBearerToken, ValidatedBearer,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tower::ServiceExt;
+
+/// Counter of how many times handlers were reached
+#[derive(Clone, Default)]
+struct Hits(Arc<AtomicUsize>);
+
+impl Hits {
+    fn get(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+async fn send(app: &Router, uri: &str, authorization: Option<&str>) -> Response {
+    let mut req = Request::get(uri);
+    if let Some(authorization) = authorization {
+        req = req.header(header::AUTHORIZATION, authorization);
+    }
+    app.clone()
+        .oneshot(req.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+}
+
+/// Router whose handlers count how often they're reached, protected by bearer tokens
+fn protected(hits: &Hits) -> Router {
+    let handler = |hits: Hits| {
+        move |Extension(AuthBearer(token)): Extension<AuthBearer>| async move {
+            hits.0.fetch_add(1, Ordering::SeqCst);
+            token.expose_secret().to_string()
+        }
+    };
+    Router::new()
+        .route("/a", get(handler(hits.clone())))
+        .route("/b", get(handler(hits.clone())))
+        .route_layer(RequireAuthLayer::<AuthBearer>::new())
+}
+
+#[tokio::test]
+async fn rejected_requests_never_reach_handlers() {
+    let hits = Hits::default();
+    let app = protected(&hits);
+    for authorization in [None, Some("Bearer "), Some("Basic YWRtaW46aHVudGVyMg==")] {
+        for uri in ["/a", "/b"] {
+            let res = send(&app, uri, authorization).await;
+            assert_eq!(
+                res.status(),
+                StatusCode::UNAUTHORIZED,
+                "{:?}",
+                authorization
+            );
+            assert!(res.headers().contains_key(header::WWW_AUTHENTICATE));
+        }
+    }
+    assert_eq!(hits.get(), 0);
+}
+
+#[tokio::test]
+async fn accepted_requests_carry_the_extractor() {
+    let hits = Hits::default();
+    let app = protected(&hits);
+    let res = send(&app, "/a", Some("Bearer s3cr3t")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(hits.get(), 1);
+
+    // Unknown routes still aren't found rather than being challenged
+    let res = send(&app, "/c", None).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(hits.get(), 1);
+}
+
+#[tokio::test]
+async fn layers_use_the_config_and_state() {
+    async fn handler(Extension(AuthBasic((id, _))): Extension<AuthBasic>) -> String {
+        id
+    }
+    let app = Router::new()
+        .route("/", get(handler))
+        .route_layer(RequireAuthLayer::<AuthBasic>::new())
+        .layer(Extension(AuthConfig::new().realm("admin")));
+    let res = send(&app, "/", None).await;
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Basic realm="admin", charset="UTF-8""#
+    );
+
+    async fn open() -> &'static str {
+        "open"
+    }
+    let validator = StaticBearerToken::new("s3cr3t");
+    let app = Router::new()
+        .route("/", get(open))
+        .route_layer(
+            RequireAuthLayer::<ValidatedBearer<StaticBearerToken>, _>::with_state(validator),
+        );
+    assert_eq!(
+        send(&app, "/", Some("Bearer s3cr3t")).await.status(),
+        StatusCode::OK
+    );
+    let res = send(&app, "/", Some("Bearer 0ld-s3cr3t")).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Bearer realm="Restricted", error="invalid_token""#
+    );
+}
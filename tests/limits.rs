@@ -0,0 +1,168 @@
+#![cfg(all(feature = "auth-basic", feature = "auth-bearer"))]
+
+use axum::{
+    body::{Body, HttpBody},
+    extract::{FromRequest, FromRequestParts},
+    http::{header, request::Builder, Request},
+    routing::get,
+    Extension, Router,
+};
+use axum_auth::{AuthBasic, AuthBearer, AuthBearerBody, AuthConfig, AuthError, BearerQueryLayer};
+use tower::ServiceExt;
+
+/// Extracts basic credentials from a header with `config`
+async fn basic(config: AuthConfig, credentials: &str) -> Result<String, AuthError> {
+    let value = format!("Basic {}", base64::encode(credentials));
+    let (mut parts, _) = Request::builder()
+        .header(header::AUTHORIZATION, value)
+        .extension(config)
+        .body(())
+        .unwrap()
+        .into_parts();
+    AuthBasic::from_request_parts(&mut parts, &())
+        .await
+        .map(|AuthBasic((id, _))| id)
+        .map_err(|rejection| rejection.into_error())
+}
+
+/// Extracts a bearer token from a request with `config`
+async fn bearer(config: AuthConfig, req: Builder) -> Result<String, AuthError> {
+    let (mut parts, _) = req.extension(config).body(()).unwrap().into_parts();
+    AuthBearer::from_request_parts(&mut parts, &())
+        .await
+        .map(|AuthBearer(token)| token.expose_secret().to_string())
+        .map_err(|rejection| rejection.into_error())
+}
+
+#[tokio::test]
+async fn headers_are_limited() {
+    let config = AuthConfig::new().max_header_len(18);
+    let ok = Request::builder().header(header::AUTHORIZATION, "Bearer 12345678901");
+    assert_eq!(
+        bearer(config.clone(), ok).await,
+        Ok("12345678901".to_string())
+    );
+    let long = Request::builder().header(header::AUTHORIZATION, "Bearer 123456789012");
+    assert_eq!(bearer(config.clone(), long).await, Err(AuthError::TooLarge));
+
+    // Basic credentials are measured once they're encoded, i.e. `Basic ` and 12 base64 characters
+    assert_eq!(
+        basic(config.clone(), "user:pas").await,
+        Ok("user".to_string())
+    );
+    assert_eq!(basic(config, "user:passw").await, Err(AuthError::TooLarge));
+}
+
+#[tokio::test]
+async fn basic_credentials_are_limited() {
+    let config = AuthConfig::new().max_basic_len(8);
+    assert_eq!(
+        basic(config.clone(), "ab:12345").await,
+        Ok("ab".to_string())
+    );
+    assert_eq!(
+        basic(config.clone(), "ab:123456").await,
+        Err(AuthError::TooLarge)
+    );
+    assert_eq!(
+        basic(config, &"a".repeat(1000)).await,
+        Err(AuthError::TooLarge)
+    );
+}
+
+#[tokio::test]
+async fn usernames_are_limited() {
+    let config = AuthConfig::new().max_username_len(4);
+    assert_eq!(
+        basic(config.clone(), "abcd:hunter2").await,
+        Ok("abcd".to_string())
+    );
+    assert_eq!(
+        basic(config.clone(), "abcde:hunter2").await,
+        Err(AuthError::TooLarge)
+    );
+    assert_eq!(basic(config, "abcde").await, Err(AuthError::TooLarge));
+}
+
+#[tokio::test]
+async fn passwords_are_limited() {
+    let config = AuthConfig::new().max_password_len(4);
+    assert_eq!(
+        basic(config.clone(), "a-long-username:1234").await,
+        Ok("a-long-username".to_string())
+    );
+    assert_eq!(basic(config, "admin:12345").await, Err(AuthError::TooLarge));
+}
+
+#[tokio::test]
+async fn header_tokens_are_limited() {
+    let config = AuthConfig::new().max_token_len(8);
+    let ok = Request::builder().header(header::AUTHORIZATION, "Bearer 12345678");
+    assert_eq!(bearer(config.clone(), ok).await, Ok("12345678".to_string()));
+    let long = Request::builder().header(header::AUTHORIZATION, "Bearer 123456789");
+    assert_eq!(bearer(config, long).await, Err(AuthError::TooLarge));
+}
+
+#[tokio::test]
+async fn query_tokens_are_limited_as_sent() {
+    async fn handler(AuthBearer(token): AuthBearer) -> String {
+        token.expose_secret().to_string()
+    }
+    let app: Router = Router::new()
+        .route("/", get(handler))
+        .layer(Extension(
+            AuthConfig::new().bearer_query(true).max_token_len(8),
+        ))
+        .layer(BearerQueryLayer::new());
+    let send = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let req = Request::get(uri).body(Body::empty()).unwrap();
+            let mut res = app.oneshot(req).await.unwrap();
+            let body = res.body_mut().data().await.unwrap().unwrap();
+            String::from_utf8(body.to_vec()).unwrap()
+        }
+    };
+
+    assert_eq!(send("/?access_token=12345678").await, "12345678");
+    assert_eq!(
+        send("/?access_token=123456789").await,
+        AuthError::TooLarge.to_string()
+    );
+
+    // Tokens are measured before they're decoded, whilst other parameters aren't limited
+    assert_eq!(
+        send("/?access_token=%31%32%33").await,
+        AuthError::TooLarge.to_string()
+    );
+    assert_eq!(
+        send("/?search=a-very-long-search&access_token=1234").await,
+        "1234"
+    );
+}
+
+#[tokio::test]
+async fn form_tokens_are_limited_as_sent() {
+    async fn extract(form: &'static str) -> Result<String, String> {
+        let req = Request::post("/")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .extension(AuthConfig::new().max_token_len(8))
+            .body(Body::from(form))
+            .unwrap();
+        match AuthBearerBody::from_request(req, &()).await {
+            Ok(AuthBearerBody(token, _)) => Ok(token.expose_secret().to_string()),
+            Err(res) => {
+                let body = res.into_body().data().await.unwrap().unwrap();
+                Err(String::from_utf8(body.to_vec()).unwrap())
+            }
+        }
+    }
+
+    let too_large = Err(AuthError::TooLarge.to_string());
+    assert_eq!(
+        extract("access_token=12345678").await,
+        Ok("12345678".to_string())
+    );
+    assert_eq!(extract("access_token=123456789").await, too_large);
+    assert_eq!(extract("access_token=%31%32%33").await, too_large);
+}
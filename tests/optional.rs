@@ -0,0 +1,107 @@
+#![cfg(all(feature = "auth-basic", feature = "auth-bearer"))]
+
+use axum::{
+    extract::FromRequestParts,
+    http::{Request, StatusCode},
+    response::IntoResponse,
+};
+use axum_auth::{AuthBasic, AuthBearer, AuthConfig, AuthError, AuthRejection, OptionalAuth};
+
+/// Extracts an optional `T` from a request with an `Authorization` header if there is one
+async fn extract<T>(
+    authorization: Option<&str>,
+    config: AuthConfig,
+) -> Result<Option<T>, AuthRejection>
+where
+    T: FromRequestParts<(), Rejection = AuthRejection>,
+{
+    let mut req = Request::builder().extension(config);
+    if let Some(authorization) = authorization {
+        req = req.header("Authorization", authorization);
+    }
+    let (mut parts, _) = req.body(()).unwrap().into_parts();
+    OptionalAuth::<T>::from_request_parts(&mut parts, &())
+        .await
+        .map(|OptionalAuth(auth)| auth)
+}
+
+#[tokio::test]
+async fn missing_credentials_give_none() {
+    let bearer = extract::<AuthBearer>(None, AuthConfig::new()).await;
+    assert_eq!(bearer.unwrap(), None);
+    let basic = extract::<AuthBasic>(None, AuthConfig::new()).await;
+    assert_eq!(basic.unwrap(), None);
+}
+
+#[tokio::test]
+async fn credentials_are_passed_through() {
+    let bearer = extract::<AuthBearer>(Some("Bearer s3cr3t"), AuthConfig::new()).await;
+    assert_eq!(bearer.unwrap(), Some(AuthBearer("s3cr3t".into())));
+
+    // `admin:hunter2`
+    let basic = extract::<AuthBasic>(Some("Basic YWRtaW46aHVudGVyMg=="), AuthConfig::new()).await;
+    let AuthBasic((id, _)) = basic.unwrap().unwrap();
+    assert_eq!(id, "admin");
+}
+
+#[tokio::test]
+async fn malformed_credentials_are_rejected_like_the_inner_extractor() {
+    for (authorization, challenge) in [
+        (
+            "Bearer ",
+            r#"Bearer realm="Restricted", error="invalid_request""#,
+        ),
+        ("Basic s3cr3t", r#"Bearer realm="Restricted""#),
+    ] {
+        let rejection = extract::<AuthBearer>(Some(authorization), AuthConfig::new())
+            .await
+            .unwrap_err();
+        let res = rejection.into_response();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED, "{}", authorization);
+        assert_eq!(
+            res.headers()["WWW-Authenticate"],
+            challenge,
+            "{}",
+            authorization
+        );
+    }
+
+    let rejection = extract::<AuthBasic>(Some("Basic !!!"), AuthConfig::new())
+        .await
+        .unwrap_err();
+    assert_eq!(rejection.error(), &AuthError::InvalidBase64);
+    let res = rejection.into_response();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        res.headers()["WWW-Authenticate"],
+        r#"Basic realm="Restricted", charset="UTF-8""#
+    );
+
+    let rejection = extract::<AuthBasic>(Some("Bearer s3cr3t"), AuthConfig::new())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        rejection.error(),
+        &AuthError::WrongScheme { expected: "Basic" }
+    );
+}
+
+#[tokio::test]
+async fn rejections_keep_the_configured_status_and_realm() {
+    let config = AuthConfig::new()
+        .realm("admin")
+        .status_mapping(|_| StatusCode::BAD_REQUEST);
+    let rejection = extract::<AuthBearer>(Some("Basic s3cr3t"), config.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        rejection.error(),
+        &AuthError::WrongScheme { expected: "Bearer" }
+    );
+    let res = rejection.into_response();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(res.headers()["WWW-Authenticate"], r#"Bearer realm="admin""#);
+
+    // Missing credentials are let through whatever status they'd be sent with
+    assert_eq!(extract::<AuthBearer>(None, config).await.unwrap(), None);
+}
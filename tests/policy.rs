@@ -0,0 +1,117 @@
+#![cfg(feature = "auth-bearer")]
+
+use axum::{
+    body::Body,
+    http::{header, request::Parts, Request, StatusCode},
+    response::Response,
+    routing::get,
+    Extension, Router,
+};
+use axum_auth::{
+    all, any, has_claim, has_role, not, AuthBearer, AuthConfig, AuthError, Authorized, HasClaims,
+    HasRoles, Policy,
+};
+use std::borrow::Cow;
+use tower::ServiceExt;
+
+type Check = fn(&AuthBearer, &Parts) -> Result<(), AuthError>;
+
+/// Policy which denies requests with whatever error their token names
+fn by_token(AuthBearer(token): &AuthBearer, _: &Parts) -> Result<(), AuthError> {
+    match token.expose_secret().as_str() {
+        "ok" => Ok(()),
+        "forbidden" => Err(AuthError::forbidden("not allowed")),
+        "unavailable" => Err(AuthError::Unavailable),
+        _ => Err(AuthError::InvalidToken),
+    }
+}
+
+async fn send(config: AuthConfig, token: Option<&str>) -> Response {
+    async fn handler(_: Authorized<AuthBearer, Check>) -> &'static str {
+        "ok"
+    }
+    let app = Router::new()
+        .route("/", get(handler))
+        .layer(Extension(config))
+        .with_state(by_token as Check);
+    let mut req = Request::get("/");
+    if let Some(token) = token {
+        req = req.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+#[tokio::test]
+async fn policy_errors_never_need_a_challenge() {
+    for (token, status) in [
+        ("ok", StatusCode::OK),
+        ("forbidden", StatusCode::FORBIDDEN),
+        ("unavailable", StatusCode::SERVICE_UNAVAILABLE),
+        ("revoked", StatusCode::FORBIDDEN),
+    ] {
+        let res = send(AuthConfig::new(), Some(token)).await;
+        assert_eq!(res.status(), status, "{}", token);
+        assert!(!res.headers().contains_key(header::WWW_AUTHENTICATE));
+    }
+}
+
+#[tokio::test]
+async fn wrapped_extractor_errors_keep_their_challenge() {
+    let res = send(AuthConfig::new(), None).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert!(res.headers().contains_key(header::WWW_AUTHENTICATE));
+}
+
+#[tokio::test]
+async fn policy_errors_use_configured_statuses() {
+    let config = AuthConfig::new().status_mapping(|err| match err {
+        AuthError::Forbidden { .. } => StatusCode::NOT_FOUND,
+        _ => StatusCode::UNAUTHORIZED,
+    });
+    let res = send(config.clone(), Some("forbidden")).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    let res = send(config, Some("unavailable")).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+}
+
+/// User with a department claim and some roles
+struct User(&'static str, &'static [&'static str]);
+
+impl HasRoles for User {
+    fn has_role(&self, role: &str) -> bool {
+        self.1.contains(&role)
+    }
+}
+
+impl HasClaims for User {
+    fn claim(&self, name: &str) -> Option<Cow<'_, str>> {
+        (name == "department").then_some(Cow::Borrowed(self.0))
+    }
+}
+
+#[tokio::test]
+async fn combinators_are_exported_from_the_crate_root() {
+    // Admins, or staff who aren't suspended
+    let policy = any(
+        has_role("admin"),
+        all(has_claim("department", "staff"), not(has_role("suspended"))),
+    );
+    let (mut parts, _) = Request::new(()).into_parts();
+    for (user, allowed) in [
+        (User("sales", &["admin"]), true),
+        (User("staff", &[]), true),
+        (User("staff", &["suspended"]), false),
+        (User("sales", &[]), false),
+    ] {
+        let result = policy.check(&user, &mut parts).await;
+        assert_eq!(result.is_ok(), allowed, "{} {:?}", user.0, user.1);
+    }
+
+    let denied = policy.check(&User("sales", &[]), &mut parts).await;
+    assert_eq!(
+        denied,
+        Err(AuthError::forbidden(
+            "requires the `admin` role, or requires `department` to be `staff`"
+        ))
+    );
+}
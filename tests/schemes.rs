@@ -0,0 +1,151 @@
+#![cfg(all(feature = "auth-basic", feature = "auth-bearer"))]
+
+use axum::{extract::FromRequestParts, http::Request};
+use axum_auth::{AuthBasic, AuthBearer, AuthConfig, AuthError};
+
+/// Extracts a bearer token from an `Authorization` header with `config`
+async fn bearer(config: AuthConfig, authorization: &str) -> Result<String, AuthError> {
+    let (mut parts, _) = Request::builder()
+        .header("Authorization", authorization)
+        .extension(config)
+        .body(())
+        .unwrap()
+        .into_parts();
+    AuthBearer::from_request_parts(&mut parts, &())
+        .await
+        .map(|AuthBearer(token)| token.expose_secret().to_string())
+        .map_err(|rejection| rejection.into_error())
+}
+
+/// Extracts basic credentials from an `Authorization` header with `config`
+async fn basic(config: AuthConfig, authorization: &str) -> Result<String, AuthError> {
+    let (mut parts, _) = Request::builder()
+        .header("Authorization", authorization)
+        .extension(config)
+        .body(())
+        .unwrap()
+        .into_parts();
+    AuthBasic::from_request_parts(&mut parts, &())
+        .await
+        .map(|AuthBasic((id, _))| id)
+        .map_err(|rejection| rejection.into_error())
+}
+
+#[tokio::test]
+async fn strict_tokens_follow_b64token() {
+    let config = AuthConfig::new().strict_bearer(true);
+    for (authorization, token) in [
+        ("Bearer abc", "abc"),
+        ("Bearer aZ09-._~+/", "aZ09-._~+/"),
+        ("Bearer abc==", "abc=="),
+        ("Bearer   abc", "abc"),
+        ("Bearer \tabc\t ", "abc"),
+        ("Bearer  \t abc \t", "abc"),
+    ] {
+        assert_eq!(
+            bearer(config.clone(), authorization).await,
+            Ok(token.to_string()),
+            "{:?}",
+            authorization
+        );
+    }
+
+    for authorization in [
+        "Bearer a b",
+        "Bearer a\tb",
+        "Bearer abc=def",
+        "Bearer ===",
+        "Bearer a,b",
+        "Bearer \"abc\"",
+    ] {
+        assert_eq!(
+            bearer(config.clone(), authorization).await,
+            Err(AuthError::MalformedToken),
+            "{:?}",
+            authorization
+        );
+    }
+    assert_eq!(
+        bearer(config, "Bearer \t ").await,
+        Err(AuthError::EmptyToken)
+    );
+}
+
+#[tokio::test]
+async fn lenient_tokens_are_taken_as_is() {
+    let config = AuthConfig::new();
+    for (authorization, token) in [
+        ("Bearer abc", "abc"),
+        ("Bearer a b", "a b"),
+        ("Bearer  abc", " abc"),
+        ("Bearer abc=def", "abc=def"),
+        ("Bearer \"abc\"", "\"abc\""),
+    ] {
+        assert_eq!(
+            bearer(config.clone(), authorization).await,
+            Ok(token.to_string()),
+            "{:?}",
+            authorization
+        );
+    }
+    assert_eq!(bearer(config, "Bearer ").await, Err(AuthError::EmptyToken));
+}
+
+#[tokio::test]
+async fn schemes_are_case_insensitive_by_default() {
+    let config = AuthConfig::new();
+    for authorization in ["Bearer abc", "bearer abc", "BEARER abc", "bEaReR abc"] {
+        assert_eq!(
+            bearer(config.clone(), authorization).await,
+            Ok("abc".to_string()),
+            "{:?}",
+            authorization
+        );
+    }
+
+    // `admin:hunter2`
+    for scheme in ["Basic", "basic", "BASIC"] {
+        let authorization = format!("{} YWRtaW46aHVudGVyMg==", scheme);
+        assert_eq!(
+            basic(config.clone(), &authorization).await,
+            Ok("admin".to_string()),
+            "{:?}",
+            authorization
+        );
+    }
+
+    // Other schemes are still told apart
+    assert_eq!(
+        bearer(config.clone(), "Basic abc").await,
+        Err(AuthError::WrongScheme { expected: "Bearer" })
+    );
+    assert_eq!(
+        basic(config, "bearer abc").await,
+        Err(AuthError::WrongScheme { expected: "Basic" })
+    );
+}
+
+#[tokio::test]
+async fn schemes_can_be_matched_exactly() {
+    let config = AuthConfig::new().case_insensitive_schemes(false);
+    assert_eq!(
+        bearer(config.clone(), "Bearer abc").await,
+        Ok("abc".to_string())
+    );
+    assert_eq!(
+        basic(config.clone(), "Basic YWRtaW46aHVudGVyMg==").await,
+        Ok("admin".to_string())
+    );
+    for authorization in ["bearer abc", "BEARER abc"] {
+        assert_eq!(
+            bearer(config.clone(), authorization).await,
+            Err(AuthError::WrongScheme { expected: "Bearer" }),
+            "{:?}",
+            authorization
+        );
+    }
+    assert_eq!(
+        basic(config, "BASIC YWRtaW46aHVudGVyMg==").await,
+        Err(AuthError::WrongScheme { expected: "Basic" })
+    );
+}
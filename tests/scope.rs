@@ -0,0 +1,120 @@
+#![cfg(feature = "auth-bearer")]
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    response::Response,
+    routing::get,
+    Router,
+};
+use axum_auth::{
+    AuthError, BearerValidator, HasScopes, RequireAuthLayer, RequireScopes, RequiredScopes,
+    ValidatedBearer,
+};
+use tower::ServiceExt;
+
+/// Principal granted whichever scopes its token lists, separated by `+`
+#[derive(Clone)]
+struct Scopes(Vec<String>);
+
+impl HasScopes for Scopes {
+    fn has_scope(&self, scope: &str) -> bool {
+        self.0.iter().any(|own| own == scope)
+    }
+}
+
+/// Validator which accepts any token but `bad`
+#[derive(Clone)]
+struct Validator;
+
+#[async_trait]
+impl BearerValidator for Validator {
+    type Principal = Scopes;
+
+    async fn validate(&self, token: &str) -> Result<Scopes, AuthError> {
+        match token {
+            "bad" => Err(AuthError::InvalidToken),
+            _ => Ok(Scopes(token.split('+').map(str::to_string).collect())),
+        }
+    }
+}
+
+struct ReadWrite;
+
+impl RequiredScopes for ReadWrite {
+    const SCOPES: &'static [&'static str] = &["repo:read", "repo:write"];
+}
+
+type Auth = RequireScopes<ValidatedBearer<Validator>, ReadWrite>;
+
+async fn handler(RequireScopes(ValidatedBearer(scopes), _): Auth) -> String {
+    scopes.0.join(" ")
+}
+
+async fn send(app: Router, token: &str) -> Response {
+    let req = Request::get("/")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    app.oneshot(req).await.unwrap()
+}
+
+fn routes() -> Router {
+    Router::new().route("/", get(handler)).with_state(Validator)
+}
+
+#[tokio::test]
+async fn every_scope_lets_requests_through() {
+    for token in ["repo:read+repo:write", "repo:write+admin+repo:read"] {
+        let res = send(routes(), token).await;
+        assert_eq!(res.status(), StatusCode::OK, "{}", token);
+    }
+}
+
+#[tokio::test]
+async fn missing_scopes_are_forbidden() {
+    for token in ["repo:read", "repo:write", "admin", "repo:read+repo:writes"] {
+        let res = send(routes(), token).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN, "{}", token);
+        assert_eq!(
+            res.headers()[header::WWW_AUTHENTICATE],
+            r#"Bearer realm="Restricted", error="insufficient_scope", scope="repo:read repo:write""#,
+            "{}",
+            token
+        );
+    }
+}
+
+#[tokio::test]
+async fn invalid_tokens_are_rejected_before_scopes_are_checked() {
+    let res = send(routes(), "bad").await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Bearer realm="Restricted", error="invalid_token""#
+    );
+}
+
+#[tokio::test]
+async fn scopes_can_be_required_by_layers() {
+    async fn open() -> &'static str {
+        "open"
+    }
+    let app = Router::new()
+        .route("/", get(open))
+        .route_layer(RequireAuthLayer::<Auth, _>::with_state(Validator));
+
+    assert_eq!(
+        send(app.clone(), "repo:read+repo:write").await.status(),
+        StatusCode::OK
+    );
+    let res = send(app, "repo:read").await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    let challenge = res.headers()[header::WWW_AUTHENTICATE].to_str().unwrap();
+    assert!(
+        challenge.contains(r#"error="insufficient_scope""#),
+        "{}",
+        challenge
+    );
+}
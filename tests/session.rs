@@ -0,0 +1,239 @@
+#![cfg(feature = "auth-session")]
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    response::Response,
+    routing::get,
+    Router,
+};
+use axum_auth::{
+    AuthError, AuthSession, AuthStoredSession, MemoryStore, SessionCookies, SessionIdSource,
+    StoredSessions,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tower::ServiceExt;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+struct User {
+    name: String,
+    admin: bool,
+}
+
+fn alice() -> User {
+    User {
+        name: "alice".to_string(),
+        admin: false,
+    }
+}
+
+fn signed() -> SessionCookies {
+    SessionCookies::signed(&[7; 32])
+}
+
+fn encrypted() -> SessionCookies {
+    SessionCookies::encrypted(&[7; 32])
+}
+
+/// Issues a cookie for `data`, giving back just its value
+fn issue(cookies: &SessionCookies, data: &User) -> String {
+    let cookie = cookies.issue(data).unwrap();
+    let header = cookie.header_value().to_str().unwrap();
+    let (pair, _) = header.split_once(';').unwrap();
+    let (_, value) = pair.split_once('=').unwrap();
+    value.to_string()
+}
+
+/// Changes a character in the middle of some base64
+fn tamper(value: &str) -> String {
+    let mid = value.len() / 2;
+    let replacement = if &value[mid..=mid] == "A" { "B" } else { "A" };
+    format!("{}{}{}", &value[..mid], replacement, &value[mid + 1..])
+}
+
+async fn send(cookies: SessionCookies, cookie: Option<String>) -> Response {
+    async fn handler(AuthSession(user): AuthSession<User>) -> String {
+        user.name
+    }
+    let app = Router::new().route("/", get(handler)).with_state(cookies);
+    let mut req = Request::get("/");
+    if let Some(cookie) = cookie {
+        req = req.header(header::COOKIE, cookie);
+    }
+    app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+#[test]
+fn sessions_round_trip() {
+    for cookies in [signed(), encrypted()] {
+        let value = issue(&cookies, &alice());
+        assert_eq!(cookies.read::<User>(&value).unwrap(), alice());
+    }
+}
+
+#[test]
+fn signed_sessions_reject_tampering() {
+    let cookies = signed();
+    let value = issue(&cookies, &alice());
+    let (payload, signature) = value.split_once('.').unwrap();
+
+    // Changing either half breaks the signature
+    for tampered in [
+        format!("{}.{}", tamper(payload), signature),
+        format!("{}.{}", payload, tamper(signature)),
+        payload.to_string(),
+        format!("{}.", payload),
+    ] {
+        assert_eq!(
+            cookies.read::<User>(&tampered),
+            Err(AuthError::InvalidSession)
+        );
+    }
+
+    // As does swapping in another session's data, or signing it with another key
+    let admin = User {
+        admin: true,
+        ..alice()
+    };
+    let other = issue(&cookies, &admin);
+    let (other_payload, _) = other.split_once('.').unwrap();
+    let swapped = format!("{}.{}", other_payload, signature);
+    assert_eq!(
+        cookies.read::<User>(&swapped),
+        Err(AuthError::InvalidSession)
+    );
+    let forged = issue(&SessionCookies::signed(&[8; 32]), &admin);
+    assert_eq!(
+        cookies.read::<User>(&forged),
+        Err(AuthError::InvalidSession)
+    );
+}
+
+#[test]
+fn encrypted_sessions_reject_tampering() {
+    let cookies = encrypted();
+    let value = issue(&cookies, &alice());
+    for tampered in [
+        tamper(&value),
+        value[..value.len() - 4].to_string(),
+        String::new(),
+    ] {
+        assert_eq!(
+            cookies.read::<User>(&tampered),
+            Err(AuthError::InvalidSession)
+        );
+    }
+    let forged = issue(&SessionCookies::encrypted(&[8; 32]), &alice());
+    assert_eq!(
+        cookies.read::<User>(&forged),
+        Err(AuthError::InvalidSession)
+    );
+}
+
+#[test]
+fn sessions_are_bound_to_their_cookie_name() {
+    for (cookies, renamed) in [
+        (signed(), signed().name("other")),
+        (encrypted(), encrypted().name("other")),
+    ] {
+        let value = issue(&cookies, &alice());
+        assert_eq!(renamed.read::<User>(&value), Err(AuthError::InvalidSession));
+    }
+}
+
+#[test]
+fn signed_and_encrypted_sessions_dont_mix() {
+    let value = issue(&signed(), &alice());
+    assert_eq!(
+        encrypted().read::<User>(&value),
+        Err(AuthError::InvalidSession)
+    );
+    let value = issue(&encrypted(), &alice());
+    assert_eq!(
+        signed().read::<User>(&value),
+        Err(AuthError::InvalidSession)
+    );
+}
+
+#[test]
+fn sessions_expire() {
+    for cookies in [signed(), encrypted()] {
+        let cookies = cookies.max_age(Duration::ZERO);
+        let value = issue(&cookies, &alice());
+        assert_eq!(cookies.read::<User>(&value), Err(AuthError::ExpiredSession));
+    }
+}
+
+#[tokio::test]
+async fn extractor_accepts_sessions() {
+    for cookies in [signed(), encrypted()] {
+        let cookie = format!("session={}", issue(&cookies, &alice()));
+        let res = send(cookies, Some(cookie)).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn extractor_rejections_have_a_challenge() {
+    for cookies in [signed(), encrypted()] {
+        let value = issue(&cookies, &alice());
+        let expired = issue(&cookies.clone().max_age(Duration::ZERO), &alice());
+        for cookie in [
+            None,
+            Some(format!("session={}", tamper(&value))),
+            Some(format!("session={}", expired)),
+            Some(format!("other={}", value)),
+        ] {
+            let res = send(cookies.clone(), cookie).await;
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(
+                res.headers()[header::WWW_AUTHENTICATE],
+                r#"Cookie realm="Restricted", cookie-name="session""#
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn stored_session_rejections_have_a_challenge() {
+    async fn handler(AuthStoredSession(_, user): AuthStoredSession<User>) -> String {
+        user.name
+    }
+    for (source, expected) in [
+        (
+            SessionIdSource::Cookie,
+            &[r#"Cookie realm="Restricted", cookie-name="session_id""#][..],
+        ),
+        (
+            SessionIdSource::Bearer,
+            &[r#"Bearer realm="Restricted""#][..],
+        ),
+        (
+            SessionIdSource::CookieOrBearer,
+            &[
+                r#"Cookie realm="Restricted", cookie-name="session_id""#,
+                r#"Bearer realm="Restricted""#,
+            ][..],
+        ),
+    ] {
+        let sessions = StoredSessions::<User>::new(MemoryStore::new()).source(source);
+        let app = Router::new().route("/", get(handler)).with_state(sessions);
+        for cookie in [None, Some("session_id=unknown")] {
+            let mut req = Request::get("/");
+            if let Some(cookie) = cookie {
+                req = req.header(header::COOKIE, cookie);
+            }
+            let req = req.body(Body::empty()).unwrap();
+            let res = app.clone().oneshot(req).await.unwrap();
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+            let challenges: Vec<_> = res
+                .headers()
+                .get_all(header::WWW_AUTHENTICATE)
+                .iter()
+                .map(|value| value.to_str().unwrap())
+                .collect();
+            assert_eq!(challenges, expected, "{:?}", source);
+        }
+    }
+}
@@ -0,0 +1,237 @@
+#![cfg(feature = "auth-session")]
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    routing::get,
+    Router,
+};
+use axum_auth::{AuthStoredSession, FileStore, MemoryStore, SessionStore, StoredSessions};
+use std::{path::PathBuf, time::Duration};
+use tower::ServiceExt;
+
+/// Temporary directory for a file store, removed once dropped
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("axum-auth-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        Self(dir)
+    }
+
+    /// Reads the expiry out of the only session file
+    fn exp(&self) -> u64 {
+        let files: Vec<_> = std::fs::read_dir(&self.0)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        let json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&files[0]).unwrap()).unwrap();
+        json["exp"].as_u64().unwrap()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Checks loading, saving and deleting for a store which can't tell apart anything shorter than a second
+async fn check_basics(store: &dyn SessionStore<String>) {
+    let alice = "alice".to_string();
+    assert_eq!(store.load("abc").await.unwrap(), None);
+    assert!(!store.touch("abc", Duration::from_secs(60)).await.unwrap());
+
+    store
+        .save("abc", &alice, Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert_eq!(store.load("abc").await.unwrap(), Some(alice.clone()));
+    assert!(store.touch("abc", Duration::from_secs(60)).await.unwrap());
+    assert_eq!(store.load("abc").await.unwrap(), Some(alice.clone()));
+
+    store
+        .save("abc", &"bob".to_string(), Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert_eq!(store.load("abc").await.unwrap(), Some("bob".to_string()));
+
+    store.delete("abc").await.unwrap();
+    store.delete("abc").await.unwrap();
+    assert_eq!(store.load("abc").await.unwrap(), None);
+
+    // Sessions which have already expired are never given back or touched
+    store.save("old", &alice, Duration::ZERO).await.unwrap();
+    assert!(!store.touch("old", Duration::from_secs(60)).await.unwrap());
+    assert_eq!(store.load("old").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn memory_store_basics() {
+    check_basics(&MemoryStore::new()).await;
+}
+
+#[tokio::test]
+async fn file_store_basics() {
+    let dir = TempDir::new("basics");
+    check_basics(&FileStore::new(&dir.0)).await;
+}
+
+#[tokio::test]
+async fn memory_store_expires_and_rolls() {
+    let store = MemoryStore::new();
+    let alice = "alice".to_string();
+    store
+        .save("abc", &alice, Duration::from_millis(50))
+        .await
+        .unwrap();
+    store
+        .save("def", &alice, Duration::from_millis(50))
+        .await
+        .unwrap();
+
+    // Touching keeps a session alive past its original expiry
+    std::thread::sleep(Duration::from_millis(30));
+    assert!(store.touch("abc", Duration::from_millis(50)).await.unwrap());
+    std::thread::sleep(Duration::from_millis(30));
+    assert_eq!(store.load("abc").await.unwrap(), Some(alice));
+    assert_eq!(store.load("def").await.unwrap(), None);
+
+    // Loading an expired session removes it
+    assert_eq!(store.len(), 1);
+}
+
+#[tokio::test]
+async fn memory_store_sweeps_expired_sessions() {
+    let store = MemoryStore::new();
+    for i in 0..1024 {
+        let id = format!("old-{}", i);
+        store
+            .save(&id, &"alice".to_string(), Duration::ZERO)
+            .await
+            .unwrap();
+    }
+    assert_eq!(store.len(), 1024);
+    store
+        .save("new", &"bob".to_string(), Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert_eq!(store.len(), 1);
+}
+
+#[tokio::test]
+async fn file_store_sweeps_expired_sessions() {
+    let dir = TempDir::new("sweep");
+    let store = FileStore::new(&dir.0);
+    assert_eq!(store.sweep().await.unwrap(), 0);
+
+    for id in ["a", "b", "c"] {
+        store
+            .save(id, &"old".to_string(), Duration::ZERO)
+            .await
+            .unwrap();
+    }
+    store
+        .save("d", &"new".to_string(), Duration::from_secs(60))
+        .await
+        .unwrap();
+    std::fs::write(dir.0.join("notes.txt"), "not a session").unwrap();
+    std::fs::write(dir.0.join("junk.json"), "not a session").unwrap();
+
+    assert_eq!(store.sweep().await.unwrap(), 3);
+    assert_eq!(store.sweep().await.unwrap(), 0);
+    assert_eq!(store.load("d").await.unwrap(), Some("new".to_string()));
+    assert_eq!(std::fs::read_dir(&dir.0).unwrap().count(), 3);
+}
+
+#[tokio::test]
+async fn file_store_only_rewrites_touched_sessions_once_half_the_ttl_is_left() {
+    let dir = TempDir::new("touch");
+    let store = FileStore::new(&dir.0);
+    store
+        .save("abc", &"alice".to_string(), Duration::from_secs(100))
+        .await
+        .unwrap();
+    let exp = dir.exp();
+
+    // Plenty of time is left, so nothing's written
+    assert!(store.touch("abc", Duration::from_secs(100)).await.unwrap());
+    assert!(store.touch("abc", Duration::from_secs(150)).await.unwrap());
+    assert_eq!(dir.exp(), exp);
+
+    // Less than half is left, so the expiry's pushed back
+    assert!(store.touch("abc", Duration::from_secs(1000)).await.unwrap());
+    assert!(dir.exp() >= exp + 900);
+    assert_eq!(store.load("abc").await.unwrap(), Some("alice".to_string()));
+}
+
+#[tokio::test]
+async fn file_store_touches_never_bring_back_deleted_sessions() {
+    let dir = TempDir::new("race");
+    let store = FileStore::new(&dir.0);
+    for _ in 0..50 {
+        // Every touch rewrites the file, racing the delete
+        store
+            .save("abc", &"alice".to_string(), Duration::from_secs(10))
+            .await
+            .unwrap();
+        let (deleted, touched) = tokio::join!(
+            store.delete("abc"),
+            store.touch("abc", Duration::from_secs(1000))
+        );
+        deleted.unwrap();
+        touched.unwrap();
+        assert_eq!(store.load("abc").await.unwrap(), None);
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn file_store_sessions_are_only_readable_by_their_owner() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new("mode");
+    let store = FileStore::new(&dir.0);
+    store
+        .save("abc", &"alice".to_string(), Duration::from_secs(10))
+        .await
+        .unwrap();
+    store.touch("abc", Duration::from_secs(1000)).await.unwrap();
+    for entry in std::fs::read_dir(&dir.0).unwrap() {
+        let mode = entry.unwrap().metadata().unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
+
+#[tokio::test]
+async fn rolling_sessions_are_kept_alive_by_requests() {
+    async fn handler(AuthStoredSession(_, name): AuthStoredSession<String>) -> String {
+        name
+    }
+    for rolling in [true, false] {
+        let sessions = StoredSessions::new(MemoryStore::new())
+            .ttl(Duration::from_millis(50))
+            .rolling(rolling);
+        let (id, _) = sessions.create(&"alice".to_string()).await.unwrap();
+        let app = Router::new().route("/", get(handler)).with_state(sessions);
+        let send = || {
+            let req = Request::get("/")
+                .header(header::COOKIE, format!("session_id={}", id.expose_secret()))
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(req)
+        };
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(send().await.unwrap().status(), StatusCode::OK);
+        std::thread::sleep(Duration::from_millis(30));
+        let expected = match rolling {
+            true => StatusCode::OK,
+            false => StatusCode::UNAUTHORIZED,
+        };
+        assert_eq!(send().await.unwrap().status(), expected, "{}", rolling);
+    }
+}
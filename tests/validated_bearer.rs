@@ -0,0 +1,92 @@
+#![cfg(feature = "auth-bearer")]
+
+use async_trait::async_trait;
+use axum::{
+    body::{Body, HttpBody},
+    http::{header, Request, StatusCode},
+    response::Response,
+    routing::get,
+    Router,
+};
+use axum_auth::{AuthError, BearerValidator, ValidatedBearer};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// Validator which knows about a single service's token, and fails for `down`
+struct Services;
+
+#[async_trait]
+impl BearerValidator for Services {
+    type Principal = String;
+
+    async fn validate(&self, token: &str) -> Result<String, AuthError> {
+        match token {
+            "s3cr3t" => Ok("billing".to_string()),
+            "down" => Err(AuthError::Unavailable),
+            _ => Err(AuthError::InvalidToken),
+        }
+    }
+}
+
+async fn handler(ValidatedBearer(service): ValidatedBearer<Arc<Services>>) -> String {
+    format!("Hello, {} service", service)
+}
+
+async fn send(authorization: Option<&str>) -> Response {
+    let app = Router::new()
+        .route("/", get(handler))
+        .with_state(Arc::new(Services));
+    let mut req = Request::get("/");
+    if let Some(authorization) = authorization {
+        req = req.header(header::AUTHORIZATION, authorization);
+    }
+    app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+#[tokio::test]
+async fn valid_tokens_give_their_principal() {
+    let res = send(Some("Bearer s3cr3t")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = res.into_body().data().await.unwrap().unwrap();
+    assert_eq!(&body[..], b"Hello, billing service");
+}
+
+#[tokio::test]
+async fn invalid_tokens_are_challenged_with_invalid_token() {
+    for token in ["Bearer 0ld-s3cr3t", "Bearer S3CR3T"] {
+        let res = send(Some(token)).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED, "{}", token);
+        assert_eq!(
+            res.headers()[header::WWW_AUTHENTICATE],
+            r#"Bearer realm="Restricted", error="invalid_token""#,
+            "{}",
+            token
+        );
+    }
+}
+
+#[tokio::test]
+async fn malformed_tokens_never_reach_the_validator() {
+    let res = send(None).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Bearer realm="Restricted""#
+    );
+
+    let res = send(Some("Bearer ")).await;
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Bearer realm="Restricted", error="invalid_request""#
+    );
+}
+
+#[tokio::test]
+async fn unavailable_validators_give_no_error_code() {
+    let res = send(Some("Bearer down")).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Bearer realm="Restricted""#
+    );
+}
@@ -0,0 +1,104 @@
+#![cfg(feature = "auth-basic")]
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    extract::FromRef,
+    http::{header, Request, StatusCode},
+    response::Response,
+    routing::get,
+    Router,
+};
+use axum_auth::{AuthError, BasicVerifier, StaticBasicCredentials, VerifiedBasic};
+use tower::ServiceExt;
+
+/// Verifier whose backing database is down
+#[derive(Clone)]
+struct Down;
+
+#[async_trait]
+impl BasicVerifier for Down {
+    type User = String;
+
+    async fn verify(&self, _id: &str, _password: Option<&str>) -> Result<String, AuthError> {
+        Err(AuthError::Unavailable)
+    }
+}
+
+/// Router state which verifiers are taken out of
+#[derive(Clone)]
+struct AppState {
+    users: StaticBasicCredentials,
+    down: Down,
+}
+
+impl FromRef<AppState> for StaticBasicCredentials {
+    fn from_ref(state: &AppState) -> Self {
+        state.users.clone()
+    }
+}
+
+impl FromRef<AppState> for Down {
+    fn from_ref(state: &AppState) -> Self {
+        state.down.clone()
+    }
+}
+
+async fn users(VerifiedBasic(id): VerifiedBasic<StaticBasicCredentials>) -> String {
+    format!("Hello, {}", id)
+}
+
+async fn down(VerifiedBasic(id): VerifiedBasic<Down>) -> String {
+    id
+}
+
+async fn send(uri: &str, authorization: Option<&str>) -> Response {
+    let app = Router::new()
+        .route("/users", get(users))
+        .route("/down", get(down))
+        .with_state(AppState {
+            users: StaticBasicCredentials::new().user("admin", "hunter2"),
+            down: Down,
+        });
+    let mut req = Request::get(uri);
+    if let Some(authorization) = authorization {
+        req = req.header(header::AUTHORIZATION, authorization);
+    }
+    app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+fn basic(credentials: &str) -> String {
+    format!("Basic {}", base64::encode(credentials))
+}
+
+#[tokio::test]
+async fn verified_users_are_let_through() {
+    let res = send("/users", Some(&basic("admin:hunter2"))).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn unverified_users_are_challenged_again() {
+    for credentials in ["admin:hunter3", "root:hunter2", "admin"] {
+        let res = send("/users", Some(&basic(credentials))).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED, "{}", credentials);
+        assert_eq!(
+            res.headers()[header::WWW_AUTHENTICATE],
+            r#"Basic realm="Restricted", charset="UTF-8""#
+        );
+    }
+
+    // Credentials are checked before the verifier gets them
+    let res = send("/down", None).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn verifier_errors_keep_their_status() {
+    let res = send("/down", Some(&basic("admin:hunter2"))).await;
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(
+        res.headers()[header::WWW_AUTHENTICATE],
+        r#"Basic realm="Restricted", charset="UTF-8""#
+    );
+}
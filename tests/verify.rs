@@ -0,0 +1,95 @@
+#![cfg(all(feature = "auth-basic", feature = "auth-bearer"))]
+
+use axum_auth::{
+    constant_time_eq, AuthError, BasicVerifier, BearerValidator, StaticBasicCredentials,
+    StaticBearerToken,
+};
+
+#[test]
+fn constant_time_eq_compares_contents() {
+    assert!(constant_time_eq("s3cr3t", "s3cr3t"));
+    assert!(constant_time_eq(b"", b""));
+    assert!(constant_time_eq(vec![0, 1, 2], [0, 1, 2]));
+    assert!(!constant_time_eq("s3cr3t", "s3cr3T"));
+    assert!(!constant_time_eq("s3cr3t", "s3cr3"));
+    assert!(!constant_time_eq("s3cr3t", "s3cr3t "));
+    assert!(!constant_time_eq("", "s3cr3t"));
+}
+
+fn users() -> StaticBasicCredentials {
+    StaticBasicCredentials::new()
+        .user("admin", "hunter2")
+        .user("ci", "0ae1f3c9")
+        .user("backup", "c0rrect-h0rse")
+}
+
+#[tokio::test]
+async fn static_basic_credentials_accept_matching_users() {
+    let users = users();
+    for (id, password) in [
+        ("admin", "hunter2"),
+        ("ci", "0ae1f3c9"),
+        ("backup", "c0rrect-h0rse"),
+    ] {
+        assert_eq!(
+            users.verify(id, Some(password)).await,
+            Ok(id.to_string()),
+            "{}",
+            id
+        );
+    }
+}
+
+#[tokio::test]
+async fn static_basic_credentials_reject_everything_else() {
+    let users = users();
+    for (id, password) in [
+        // Wrong passwords, including other users' ones
+        ("admin", Some("hunter3")),
+        ("admin", Some("0ae1f3c9")),
+        ("backup", Some("c0rrect-h0rs")),
+        ("admin", Some("")),
+        ("admin", None),
+        // Unknown users, or known users spelt differently
+        ("root", Some("hunter2")),
+        ("Admin", Some("hunter2")),
+        ("", Some("")),
+    ] {
+        assert_eq!(
+            users.verify(id, password).await,
+            Err(AuthError::InvalidCredentials),
+            "{} {:?}",
+            id,
+            password
+        );
+    }
+
+    // Nobody gets in without any users
+    let empty = StaticBasicCredentials::new();
+    assert!(empty.verify("", Some("")).await.is_err());
+}
+
+#[tokio::test]
+async fn static_bearer_tokens_accept_any_of_theirs() {
+    let tokens = StaticBearerToken::new("n3w-s3cr3t")
+        .token("0ld-s3cr3t")
+        .token("l4st-s3cr3t");
+    for token in ["n3w-s3cr3t", "0ld-s3cr3t", "l4st-s3cr3t"] {
+        assert_eq!(tokens.validate(token).await, Ok(()), "{}", token);
+    }
+    for token in ["s3cr3t", "n3w-s3cr3", "l4st-s3cr3t ", ""] {
+        assert_eq!(
+            tokens.validate(token).await,
+            Err(AuthError::InvalidToken),
+            "{}",
+            token
+        );
+    }
+}
+
+#[test]
+fn static_verifiers_never_show_secrets() {
+    let debug = format!("{:?} {:?}", users(), StaticBearerToken::new("n3w-s3cr3t"));
+    assert!(!debug.contains("hunter2"), "{}", debug);
+    assert!(!debug.contains("n3w-s3cr3t"), "{}", debug);
+}